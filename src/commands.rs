@@ -1,6 +1,18 @@
+mod billable;
+mod entry;
+mod hook;
 mod init;
+mod invoice;
 mod log;
 mod output;
+mod pause;
+mod rate;
+mod report;
+mod resume;
+mod start;
+mod status;
+mod stop;
+mod task;
 
 use crate::state::State;
 use clap::ArgMatches;
@@ -16,6 +28,30 @@ pub fn exec(state: State) -> Result<State, Box<dyn std::error::Error>> {
         log::exec(state, submatches)
     } else if subcommand == "output" {
         output::exec(state, submatches)
+    } else if subcommand == "report" {
+        report::exec(state, submatches)
+    } else if subcommand == "invoice" {
+        invoice::exec(state, submatches)
+    } else if subcommand == "rate" {
+        rate::exec(state, submatches)
+    } else if subcommand == "billable" {
+        billable::exec(state, submatches)
+    } else if subcommand == "entry" {
+        entry::exec(state, submatches)
+    } else if subcommand == "task" {
+        task::exec(state, submatches)
+    } else if subcommand == "hook" {
+        hook::exec(state, submatches)
+    } else if subcommand == "start" {
+        start::exec(state, submatches)
+    } else if subcommand == "stop" {
+        stop::exec(state, submatches)
+    } else if subcommand == "status" {
+        status::exec(state, submatches)
+    } else if subcommand == "pause" {
+        pause::exec(state, submatches)
+    } else if subcommand == "resume" {
+        resume::exec(state, submatches)
     } else {
         Err("Unknown command".into())
     }
@@ -0,0 +1,93 @@
+use crate::project::Project;
+use crate::state::State;
+use crate::task::Task;
+use crate::utils;
+
+use clap::ArgMatches;
+use std::error;
+
+pub fn exec<'a>(
+    state: State<'a>,
+    matches: ArgMatches<'a>,
+) -> Result<State<'a>, Box<dyn error::Error>> {
+    // Make sure we have an active project
+    if state.project.is_none() {
+        utils::error_msg("No Project Found", "Timecop requires a project to be defined before you can start\r\nusing timecop to log entries, first run: $ timecop init");
+        std::process::exit(1);
+    }
+
+    let project = state.project.as_ref().unwrap();
+    let billable = matches.value_of("billable");
+
+    match matches.value_of("task") {
+        Some(name) => {
+            let task = match project.find_task(name) {
+                Some(task) => task,
+                None => {
+                    utils::error_msg(
+                        "No Task Found",
+                        &format!("There is no task named `{}` in this project.", name),
+                    );
+                    std::process::exit(1);
+                }
+            };
+
+            match billable {
+                Some("yes") => task.set_billable(Some(true)),
+                Some("no") => task.set_billable(Some(false)),
+                Some(_) => task.set_billable(None),
+                None => {}
+            }
+
+            let task = Task::get_by_id(task.id).unwrap();
+            display_task_billable(project, &task);
+        }
+        None => {
+            match billable {
+                Some("yes") => project.set_billable(true),
+                Some("no") => project.set_billable(false),
+                Some(_) => {
+                    utils::error_msg(
+                        "Invalid Value",
+                        "Only tasks can go back to the `default`, a project is either billable or not.",
+                    );
+                    std::process::exit(1);
+                }
+                None => {}
+            }
+
+            let project = Project::get_by_id(project.id).unwrap();
+            display_billable(&project);
+        }
+    }
+
+    Ok(state)
+}
+
+// Whether the project is billable, followed by the tasks that differ
+fn display_billable(project: &Project) {
+    utils::info_msg_compact("Billable:", format_billable(project.billable));
+
+    for task in project.list_tasks().iter() {
+        if let Some(billable) = task.billable {
+            println!("  {} {}", task.name, format_billable(billable));
+        }
+    }
+}
+
+fn display_task_billable(project: &Project, task: &Task) {
+    let billable = match task.billable {
+        Some(billable) => format_billable(billable).to_string(),
+        None => format!("{} (from the project)", format_billable(project.billable)),
+    };
+
+    utils::info_msg_compact(&format!("{}:", task.name), &billable);
+}
+
+fn format_billable(billable: bool) -> &'static str {
+    if billable {
+        "yes"
+    } else {
+        "no"
+    }
+}
@@ -0,0 +1,112 @@
+mod bulk;
+mod delete;
+mod edit;
+mod list;
+
+use crate::dates;
+use crate::entry::Entry;
+use crate::project::Project;
+use crate::state::State;
+use crate::utils;
+
+use chrono::{Local, NaiveDateTime};
+use clap::ArgMatches;
+use dialoguer::{theme::ColorfulTheme, Select};
+use std::error;
+use termion::{color, style};
+
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+pub fn exec<'a>(
+    state: State<'a>,
+    matches: ArgMatches<'a>,
+) -> Result<State<'a>, Box<dyn error::Error>> {
+    // Make sure we have an active project
+    if state.project.is_none() {
+        utils::error_msg("No Project Found", "Timecop requires a project to be defined before you can start\r\nusing timecop to log entries, first run: $ timecop init");
+        std::process::exit(1);
+    }
+
+    let subcommand = super::subcommand_name(&matches)?;
+    let submatches = super::subcommand_matches(&matches, &subcommand)?;
+
+    if subcommand == "list" {
+        list::exec(state, submatches)
+    } else if subcommand == "edit" {
+        edit::exec(state, submatches)
+    } else if subcommand == "delete" {
+        delete::exec(state, submatches)
+    } else {
+        Err("Unknown command".into())
+    }
+}
+
+// Find the entry by the given id, or let the user pick one of the entries
+// of this project if no id was given.
+fn find_or_select_entry(project: &Project, id: Option<&str>) -> Option<Entry> {
+    match id {
+        Some(id) => match id.trim_start_matches('#').parse::<usize>() {
+            Ok(id) => Entry::get_for_project(project, id),
+            Err(_) => None,
+        },
+        None => select_entry(project),
+    }
+}
+
+fn select_entry(project: &Project) -> Option<Entry> {
+    let entries = project.list_entries();
+
+    if entries.is_empty() {
+        println!("There are no log entries for this project yet.");
+        std::process::exit(0);
+    }
+
+    let items: Vec<String> = entries.iter().map(format_entry_plain).collect();
+
+    let result = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Select a log entry:")
+        .default(0)
+        .items(&items)
+        .paged(true)
+        .interact_opt()
+        .unwrap();
+
+    result.map(|index| entries[index].clone())
+}
+
+fn format_entry(entry: &Entry) -> String {
+    let date = utils::utc_to_local(&entry.inserted_at);
+    format!(
+        "{}#{:<5}{} {} [{}{}{}] {}{}{}: {}",
+        color::Fg(color::LightBlack),
+        entry.id,
+        style::Reset,
+        date.format("%a %e %b %Y %H:%M"),
+        color::Fg(color::LightWhite),
+        utils::format_time(entry.minutes),
+        style::Reset,
+        style::Bold,
+        entry.task_name,
+        style::Reset,
+        entry.name
+    )
+}
+
+// The pickers from dialoguer don't cope well with escape codes
+fn format_entry_plain(entry: &Entry) -> String {
+    let date = utils::utc_to_local(&entry.inserted_at);
+    format!(
+        "#{:<5} {} [{}] {}: {}",
+        entry.id,
+        date.format("%a %e %b %Y %H:%M"),
+        utils::format_time(entry.minutes),
+        entry.task_name,
+        entry.name
+    )
+}
+
+// Dates are entered in local time, but stored in UTC
+fn parse_date(input: &str) -> Result<NaiveDateTime, String> {
+    let date = dates::parse_datetime(input, Local::now().naive_local())?;
+    utils::local_to_utc(&date).ok_or_else(|| format!("{} does not exist in your timezone", date))
+}
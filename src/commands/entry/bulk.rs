@@ -0,0 +1,323 @@
+use crate::dates;
+use crate::duration;
+use crate::entry::Entry;
+use crate::project::Project;
+use crate::task::Task;
+use crate::utils;
+
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use dialoguer::{theme::ColorfulTheme, Confirm, Editor};
+use std::collections::HashSet;
+use std::error;
+use std::vec::Vec;
+
+const HEADER: &str = "# Edit the log entries below, one entry per line:
+#
+#   id | date | minutes | task | message
+#
+# Changing a line updates the entry, removing a line deletes the entry,
+# and a line without an id (start it with `-`) adds a new entry. Dates
+# are in local time, and a `|` in a task name is written as `\\|`. Lines
+# starting with `#` are ignored, and if you remove everything nothing will
+# be changed.
+";
+
+const ERROR_PREFIX: &str = "# ERROR: ";
+
+// A single line from the editor buffer, already validated
+struct Line {
+    id: Option<usize>,
+    inserted_at: NaiveDateTime,
+    minutes: usize,
+    task: Task,
+    message: String,
+}
+
+// Dump the entries within the given range into the editor, and apply
+// whatever the user changed back to the database (like `git rebase -i`)
+pub fn edit_range(project: &Project, range: &str) -> Result<(), Box<dyn error::Error>> {
+    let (from, to) = parse_range(range)?;
+
+    // Oldest first, which reads more naturally in an editor
+    let mut entries: Vec<Entry> = project
+        .list_entries()
+        .into_iter()
+        .filter(|entry| {
+            let date = utils::utc_to_local(&entry.inserted_at).naive_local().date();
+            from.is_none_or(|from| date >= from) && to.is_none_or(|to| date <= to)
+        })
+        .collect();
+    entries.reverse();
+
+    let mut buffer = entries_to_buffer(&entries);
+
+    // Keep reopening the editor until the buffer parses, or the user gives up
+    let lines = loop {
+        let edited = match Editor::new().require_save(false).edit(&buffer)? {
+            Some(edited) => edited,
+            None => return Ok(()),
+        };
+
+        match parse_buffer(project, &entries, &edited) {
+            Ok(lines) => break lines,
+            Err(errors) => {
+                utils::error_msg(
+                    "Invalid Log Entries",
+                    &format!(
+                        "{} line(s) could not be read, they have been marked in the editor.",
+                        errors.len()
+                    ),
+                );
+
+                buffer = annotate_buffer(&edited, &errors);
+            }
+        }
+    };
+
+    // An empty buffer means the user wants to abort
+    if lines.is_empty() {
+        println!("Nothing changed.");
+        return Ok(());
+    }
+
+    apply_changes(&entries, lines);
+    Ok(())
+}
+
+// A range is written as `from..to`, either side can be left out
+fn parse_range(range: &str) -> Result<(Option<NaiveDate>, Option<NaiveDate>), String> {
+    let parts: Vec<&str> = range.splitn(2, "..").collect();
+    if parts.len() != 2 {
+        return Err(format!(
+            "Expected a range like 2021-06-01..2021-06-30, not `{}`",
+            range
+        ));
+    }
+
+    let parse = |input: &str| -> Result<Option<NaiveDate>, String> {
+        if input.trim().is_empty() {
+            return Ok(None);
+        }
+
+        dates::parse_date(input, Local::today().naive_local()).map(Some)
+    };
+
+    Ok((parse(parts[0])?, parse(parts[1])?))
+}
+
+fn entries_to_buffer(entries: &[Entry]) -> String {
+    let mut buffer = HEADER.to_string();
+    buffer.push('\n');
+
+    for entry in entries {
+        buffer.push_str(&format!(
+            "{} | {} | {} | {} | {}\n",
+            entry.id,
+            utils::utc_to_local(&entry.inserted_at).format(super::DATE_FORMAT),
+            entry.minutes,
+            entry.task_name.replace('|', "\\|"),
+            entry.name
+        ));
+    }
+
+    buffer
+}
+
+// Parse every line of the buffer, collecting all errors (by line index)
+// so they can all be pointed out in one go.
+fn parse_buffer(
+    project: &Project,
+    entries: &[Entry],
+    buffer: &str,
+) -> Result<Vec<Line>, Vec<(usize, String)>> {
+    let mut lines: Vec<Line> = Vec::new();
+    let mut errors: Vec<(usize, String)> = Vec::new();
+    let mut seen: HashSet<usize> = HashSet::new();
+
+    for (index, line) in buffer.lines().enumerate() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        match parse_line(project, line) {
+            Ok(parsed) => {
+                if let Some(id) = parsed.id {
+                    if !entries.iter().any(|e| e.id == id) {
+                        errors.push((index, format!("entry #{} is not part of this range", id)));
+                        continue;
+                    }
+
+                    if !seen.insert(id) {
+                        errors.push((index, format!("entry #{} appears more than once", id)));
+                        continue;
+                    }
+                }
+
+                lines.push(parsed);
+            }
+            Err(error) => errors.push((index, error)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(lines)
+    } else {
+        Err(errors)
+    }
+}
+
+fn parse_line(project: &Project, line: &str) -> Result<Line, String> {
+    // The message is last so it is free to contain the separator, in the
+    // task name it is escaped
+    let mut parts: Vec<&str> = line.splitn(4, '|').map(|p| p.trim()).collect();
+    match parts.pop().and_then(split_task) {
+        Some((task, message)) if parts.len() == 3 => {
+            parts.push(task);
+            parts.push(message);
+        }
+        _ => return Err("expected: id | date | minutes | task | message".to_string()),
+    }
+
+    let id = match parts[0] {
+        "" | "-" => None,
+        id => match id.trim_start_matches('#').parse::<usize>() {
+            Ok(id) => Some(id),
+            Err(_) => return Err(format!("`{}` is not a valid id", id)),
+        },
+    };
+
+    let inserted_at = super::parse_date(parts[1])?;
+
+    let minutes = duration::parse_minutes(parts[2])?;
+
+    let task_name = parts[3].replace("\\|", "|");
+    let task = match project.find_task(&task_name) {
+        Some(task) => task,
+        None => return Err(format!("there is no task named `{}`", task_name)),
+    };
+
+    if parts[4].is_empty() {
+        return Err("the message can not be empty".to_string());
+    }
+
+    Ok(Line {
+        id,
+        inserted_at,
+        minutes,
+        task,
+        message: parts[4].to_string(),
+    })
+}
+
+// Split `task | message` at the first `|` that isn't escaped with a `\`
+fn split_task(input: &str) -> Option<(&str, &str)> {
+    let index = input
+        .match_indices('|')
+        .map(|(index, _)| index)
+        .find(|index| !input[..*index].ends_with('\\'))?;
+
+    Some((input[..index].trim(), input[index + 1..].trim()))
+}
+
+// Put the errors right above the lines they are about, throwing away the
+// errors of a previous attempt so they don't pile up.
+fn annotate_buffer(buffer: &str, errors: &[(usize, String)]) -> String {
+    let mut annotated = String::new();
+
+    for (index, line) in buffer.lines().enumerate() {
+        if line.starts_with(ERROR_PREFIX) {
+            continue;
+        }
+
+        for (_, error) in errors.iter().filter(|(i, _)| *i == index) {
+            annotated.push_str(ERROR_PREFIX);
+            annotated.push_str(error);
+            annotated.push('\n');
+        }
+
+        annotated.push_str(line);
+        annotated.push('\n');
+    }
+
+    annotated
+}
+
+fn apply_changes(entries: &[Entry], lines: Vec<Line>) {
+    let mut updated: Vec<Entry> = Vec::new();
+    let mut created: Vec<Line> = Vec::new();
+
+    let kept: HashSet<usize> = lines.iter().filter_map(|l| l.id).collect();
+    let deleted: Vec<&Entry> = entries.iter().filter(|e| !kept.contains(&e.id)).collect();
+
+    for line in lines {
+        let id = match line.id {
+            Some(id) => id,
+            None => {
+                created.push(line);
+                continue;
+            }
+        };
+
+        let original = entries.iter().find(|e| e.id == id).unwrap();
+        let mut entry = original.clone();
+        entry.minutes = line.minutes;
+        entry.task_id = line.task.id;
+        entry.task_name = line.task.name;
+        entry.name = line.message;
+
+        // Dates are only shown with minute precision, so keep the seconds
+        // of the original date unless the date was actually changed.
+        if line.inserted_at.format(super::DATE_FORMAT).to_string()
+            != original.inserted_at.format(super::DATE_FORMAT).to_string()
+        {
+            entry.set_date(line.inserted_at);
+        }
+
+        if entry.inserted_at != original.inserted_at
+            || entry.minutes != original.minutes
+            || entry.task_id != original.task_id
+            || entry.name != original.name
+        {
+            updated.push(entry);
+        }
+    }
+
+    if updated.is_empty() && created.is_empty() && deleted.is_empty() {
+        println!("Nothing changed.");
+        return;
+    }
+
+    let confirmed = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!(
+            "Update {} entries, delete {} entries and add {} entries?",
+            updated.len(),
+            deleted.len(),
+            created.len()
+        ))
+        .interact()
+        .unwrap();
+
+    if !confirmed {
+        return;
+    }
+
+    for entry in &updated {
+        entry.update();
+    }
+
+    for entry in &deleted {
+        entry.delete();
+    }
+
+    for line in &created {
+        line.task.add_log_at(
+            line.minutes,
+            line.message.to_string(),
+            &line.inserted_at,
+            None,
+        );
+        line.task.touch();
+    }
+
+    println!("Done. The entries have been updated.");
+}
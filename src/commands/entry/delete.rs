@@ -0,0 +1,36 @@
+use crate::state::State;
+use crate::utils;
+
+use clap::ArgMatches;
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use std::error;
+
+pub fn exec<'a>(
+    state: State<'a>,
+    matches: ArgMatches<'a>,
+) -> Result<State<'a>, Box<dyn error::Error>> {
+    let project = state.project.as_ref().unwrap();
+
+    let entry = match super::find_or_select_entry(project, matches.value_of("id")) {
+        Some(entry) => entry,
+        None => {
+            utils::error_msg("No Entry Found", "There is no log entry with that id.");
+            std::process::exit(1);
+        }
+    };
+
+    println!("{}", super::format_entry(&entry));
+
+    let confirmed = matches.is_present("yes")
+        || Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Are you sure you want to delete this entry?")
+            .interact()
+            .unwrap();
+
+    if confirmed {
+        entry.delete();
+        println!("Done. The entry has been deleted.");
+    }
+
+    Ok(state)
+}
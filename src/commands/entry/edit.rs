@@ -0,0 +1,126 @@
+use crate::duration;
+use crate::entry::Entry;
+use crate::project::Project;
+use crate::state::State;
+use crate::utils;
+
+use clap::ArgMatches;
+use dialoguer::{theme::ColorfulTheme, Input, Select};
+use std::error;
+
+pub fn exec<'a>(
+    state: State<'a>,
+    matches: ArgMatches<'a>,
+) -> Result<State<'a>, Box<dyn error::Error>> {
+    let project = state.project.as_ref().unwrap();
+
+    // Editing a range of entries happens in the user's editor instead
+    if let Some(range) = matches.value_of("range") {
+        super::bulk::edit_range(project, range)?;
+        return Ok(state);
+    }
+
+    let mut entry = match super::find_or_select_entry(project, matches.value_of("id")) {
+        Some(entry) => entry,
+        None => {
+            utils::error_msg("No Entry Found", "There is no log entry with that id.");
+            std::process::exit(1);
+        }
+    };
+
+    println!("{}", super::format_entry(&entry));
+
+    // When any of the fields is given as an argument we only change those,
+    // otherwise we walk the user through all of them.
+    let fields = ["message", "minutes", "date", "task"];
+    if fields.iter().any(|field| matches.is_present(field)) {
+        apply_arguments(project, &mut entry, &matches)?;
+    } else {
+        prompt_fields(project, &mut entry);
+    }
+
+    entry.update();
+    println!("Done. The entry has been updated.");
+
+    Ok(state)
+}
+
+fn apply_arguments(
+    project: &Project,
+    entry: &mut Entry,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn error::Error>> {
+    if let Some(message) = matches.value_of("message") {
+        entry.name = message.to_string();
+    }
+
+    if let Some(minutes) = matches.value_of("minutes") {
+        entry.minutes = duration::parse_minutes(minutes)?;
+    }
+
+    if let Some(date) = matches.value_of("date") {
+        entry.set_date(super::parse_date(date)?);
+    }
+
+    if let Some(task) = matches.value_of("task") {
+        match project.find_task(task) {
+            Some(task) => {
+                entry.task_id = task.id;
+                entry.task_name = task.name;
+            }
+            None => return Err(format!("There is no task named `{}`", task).into()),
+        }
+    }
+
+    Ok(())
+}
+
+fn prompt_fields(project: &Project, entry: &mut Entry) {
+    let theme = ColorfulTheme::default();
+
+    entry.name = Input::with_theme(&theme)
+        .with_prompt("Message:")
+        .with_initial_text(entry.name.to_string())
+        .interact()
+        .unwrap();
+
+    let minutes: String = Input::with_theme(&theme)
+        .with_prompt("Time spent:")
+        .default(utils::format_time(entry.minutes))
+        .validate_with(|input: &String| duration::parse_minutes(input).map(|_| ()))
+        .interact()
+        .unwrap();
+    entry.minutes = duration::parse_minutes(&minutes).unwrap();
+
+    let current_date = utils::utc_to_local(&entry.inserted_at)
+        .format(super::DATE_FORMAT)
+        .to_string();
+    let date: String = Input::with_theme(&theme)
+        .with_prompt("Date:")
+        .default(current_date.to_string())
+        .validate_with(|input: &String| super::parse_date(input).map(|_| ()))
+        .interact()
+        .unwrap();
+
+    if date != current_date {
+        entry.set_date(super::parse_date(&date).unwrap());
+    }
+
+    let tasks = project.list_tasks();
+    let task_names: Vec<String> = tasks.iter().map(|t| t.name.clone()).collect();
+    let current = tasks
+        .iter()
+        .position(|t| t.id == entry.task_id)
+        .unwrap_or(0);
+
+    let result = Select::with_theme(&theme)
+        .with_prompt("Task:")
+        .default(current)
+        .items(&task_names)
+        .paged(true)
+        .interact()
+        .unwrap();
+
+    entry.task_id = tasks[result].id;
+    entry.task_name = tasks[result].name.to_string();
+}
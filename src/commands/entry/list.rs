@@ -0,0 +1,26 @@
+use crate::state::State;
+
+use clap::ArgMatches;
+use std::error;
+
+pub fn exec<'a>(
+    state: State<'a>,
+    matches: ArgMatches<'a>,
+) -> Result<State<'a>, Box<dyn error::Error>> {
+    let project = state.project.as_ref().unwrap();
+    let mut entries = project.list_entries();
+
+    if let Some(limit) = matches.value_of("limit") {
+        entries.truncate(limit.parse()?);
+    }
+
+    if entries.is_empty() {
+        println!("There are no log entries for this project yet.");
+    }
+
+    for entry in &entries {
+        println!("{}", super::format_entry(entry));
+    }
+
+    Ok(state)
+}
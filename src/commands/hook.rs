@@ -0,0 +1,36 @@
+mod install;
+mod status;
+mod uninstall;
+
+use crate::hook;
+use crate::state::State;
+use crate::utils;
+
+use clap::ArgMatches;
+use std::error;
+use std::path::PathBuf;
+
+pub fn exec<'a>(
+    state: State<'a>,
+    matches: ArgMatches<'a>,
+) -> Result<State<'a>, Box<dyn error::Error>> {
+    let subcommand = super::subcommand_name(&matches)?;
+    let submatches = super::subcommand_matches(&matches, &subcommand)?;
+
+    if subcommand == "status" {
+        status::exec(state, submatches)
+    } else if subcommand == "install" {
+        install::exec(state, submatches)
+    } else if subcommand == "uninstall" {
+        uninstall::exec(state, submatches)
+    } else {
+        Err("Unknown command".into())
+    }
+}
+
+// The hook of the repository we are in, for a submodule that is its own hook
+fn current_hook_path() -> PathBuf {
+    // The repository was checked before running any command
+    let repo = utils::get_current_repo().unwrap();
+    hook::hook_path(&repo)
+}
@@ -0,0 +1,25 @@
+use crate::hook;
+use crate::state::State;
+use crate::utils;
+
+use clap::ArgMatches;
+use std::error;
+
+pub fn exec<'a>(
+    state: State<'a>,
+    _matches: ArgMatches<'a>,
+) -> Result<State<'a>, Box<dyn error::Error>> {
+    let path = super::current_hook_path();
+
+    if let Err(err) = hook::install(&path) {
+        utils::error_msg(
+            "Hook Not Installed",
+            &format!("Failed to write `{}`: {}", path.display(), err),
+        );
+        std::process::exit(1);
+    }
+
+    println!("Done. Timecop is part of `{}` now!", path.display());
+
+    Ok(state)
+}
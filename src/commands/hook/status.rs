@@ -0,0 +1,26 @@
+use crate::hook::{self, HookState};
+use crate::state::State;
+use crate::utils;
+
+use clap::ArgMatches;
+use std::error;
+
+pub fn exec<'a>(
+    state: State<'a>,
+    _matches: ArgMatches<'a>,
+) -> Result<State<'a>, Box<dyn error::Error>> {
+    let path = super::current_hook_path();
+
+    let status = match hook::state(&path) {
+        HookState::Missing => "not installed, there is no post-commit hook yet",
+        HookState::Absent => "not installed, the post-commit hook does not run timecop",
+        HookState::Legacy => "installed by an older timecop, run `timecop hook install` to upgrade",
+        HookState::Outdated => "outdated, run `timecop hook install` to update it",
+        HookState::Installed => "installed",
+    };
+
+    utils::info_msg_compact("Hook:", &path.display().to_string());
+    utils::info_msg_compact("Status:", status);
+
+    Ok(state)
+}
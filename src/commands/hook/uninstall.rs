@@ -0,0 +1,30 @@
+use crate::hook;
+use crate::state::State;
+use crate::utils;
+
+use clap::ArgMatches;
+use std::error;
+
+pub fn exec<'a>(
+    state: State<'a>,
+    _matches: ArgMatches<'a>,
+) -> Result<State<'a>, Box<dyn error::Error>> {
+    let path = super::current_hook_path();
+
+    match hook::uninstall(&path) {
+        Ok(true) => println!("Done. Timecop was removed from `{}`.", path.display()),
+        Ok(false) => println!(
+            "Timecop is not part of `{}`, nothing to remove.",
+            path.display()
+        ),
+        Err(err) => {
+            utils::error_msg(
+                "Hook Not Uninstalled",
+                &format!("Failed to update `{}`: {}", path.display(), err),
+            );
+            std::process::exit(1);
+        }
+    }
+
+    Ok(state)
+}
@@ -1,3 +1,4 @@
+use crate::hook::{self, HookState};
 use crate::project::Project;
 use crate::state::State;
 use crate::utils;
@@ -5,12 +6,8 @@ use crate::utils;
 use clap::ArgMatches;
 use dialoguer::{theme::ColorfulTheme, Confirm, Input, Select};
 use std::error;
-use std::fs;
-use std::fs::File;
-use std::io::Write;
-use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
 use std::vec::Vec;
-use textwrap::indent;
 
 pub fn exec<'a>(
     mut state: State<'a>,
@@ -107,88 +104,44 @@ fn select_project(remote: String, projects: Vec<Project>) -> Option<Project> {
     }
 }
 
-fn hook_data() -> String {
-    format!(
-        "#!/usr/bin/env bash
-
-# Offer a nice interactive experience
-exec < /dev/tty
-exec < /dev/stdin
-exec < /dev/stderr
-
-# Start a new commit based log entry
-{} log --commit
-
-# Close stdin again
-exec <&-
-",
-        std::env::current_exe().unwrap().to_str().unwrap()
-    )
-}
-
 fn install_git_hook() {
-    let repo = utils::get_current_repo();
-    if repo.is_none() {
-        return;
-    }
+    let repo = match utils::get_current_repo() {
+        Some(repo) => repo,
+        None => return,
+    };
 
-    let hook_path = repo.unwrap().path().join("hooks/post-commit");
+    let hook_path = hook::hook_path(&repo);
 
-    if !prompt_install_hook() {
+    // Nothing to ask when timecop is already part of the hook
+    if hook::state(&hook_path) == HookState::Installed {
         return;
     }
 
-    // If there is already a post-commit hook installed, ask if the
-    // user wants to overwrite this, or not.
-    if hook_path.exists() && !prompt_overwrite_existing_hook() {
-        manual_install_hook_text();
+    if !prompt_install_hook(&hook_path) {
         return;
     }
 
-    // Try to create the file
-    let mut file = match File::create(&hook_path) {
-        Err(err) => panic!("failed to create git hook: {}", err),
-        Ok(file) => file,
-    };
-
-    // Write the hook contents
-    if let Err(why) = file.write_all(hook_data().as_bytes()) {
-        panic!("failed to create git hook: {}", why);
+    // Whatever the hook already runs stays, timecop is added to it
+    if let Err(err) = hook::install(&hook_path) {
+        utils::error_msg(
+            "Hook Not Installed",
+            &format!("Failed to write `{}`: {}", hook_path.display(), err),
+        );
+        return;
     }
 
-    // Make it executable for the user
-    fs::set_permissions(hook_path, fs::Permissions::from_mode(0o766)).unwrap();
-
-    println!("Done. The `.git/hooks/post-commit` hook has been installed!");
-}
-
-fn prompt_install_hook() -> bool {
-    Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt("Do you want to install the `.git/hooks/post-commit` hook for timecop?")
-        .interact()
-        .unwrap()
+    println!(
+        "Done. Timecop is part of `{}` now, `timecop hook uninstall` removes it again.",
+        hook_path.display()
+    );
 }
 
-fn prompt_overwrite_existing_hook() -> bool {
+fn prompt_install_hook(hook_path: &Path) -> bool {
     Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt(
-            "It looks like `.git/hooks/post-commit` already exists, do you want to overwrite it?",
-        )
+        .with_prompt(format!(
+            "Do you want to add timecop to the `{}` hook?",
+            hook_path.display()
+        ))
         .interact()
         .unwrap()
 }
-
-fn manual_install_hook_text() {
-    utils::info_msg(
-        "Manual Installation",
-        &format!(
-            "There is already a `.git/hooks/post-commit` file present and you opted to
-not overwrite this with the Timecop hook. Since you did try to install it
-originally we'll dump our `post-commit` hook here so you can pick what you
-want to upgrade your existing `post-commit` hook.
-
-{}",
-            indent(&hook_data(), "\t")
-        ),
-    )
-}
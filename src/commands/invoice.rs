@@ -0,0 +1,116 @@
+use crate::config;
+use crate::invoice::Invoice;
+use crate::render::invoice;
+use crate::state::State;
+use crate::utils;
+
+use clap::ArgMatches;
+use std::error;
+use std::fs::File;
+use std::io::{self, Write};
+
+pub fn exec<'a>(
+    state: State<'a>,
+    matches: ArgMatches<'a>,
+) -> Result<State<'a>, Box<dyn error::Error>> {
+    // Make sure we have an active project
+    if state.project.is_none() {
+        utils::error_msg("No Project Found", "Timecop requires a project to be defined before you can start\r\nusing timecop to log entries, first run: $ timecop init");
+        std::process::exit(1);
+    }
+
+    let options = super::output::summary_options(&matches);
+
+    // An invoice is always for a period with a beginning and an end
+    if options.range.from.is_none() || options.range.to.is_none() {
+        utils::error_msg(
+            "Invalid Date",
+            "An invoice needs a period, use `--from` and `--to` or one of\r\n`--last-week` and `--last-month`.",
+        );
+        std::process::exit(1);
+    }
+
+    let tax_percentage = match matches.value_of("tax") {
+        Some(tax) => match tax.trim().trim_end_matches('%').parse::<f64>() {
+            Ok(tax) if (0.0..=100.0).contains(&tax) => tax,
+            _ => {
+                utils::error_msg(
+                    "Invalid Tax",
+                    &format!(
+                        "`{}` is not a percentage between 0 and 100, write it like 21 or 8.5",
+                        tax
+                    ),
+                );
+                std::process::exit(1);
+            }
+        },
+        None => config::tax_percentage(),
+    };
+
+    // Price the time spent on every task
+    let project = state.project.as_ref().unwrap();
+    let summary = project.summary(&options);
+    let mut invoice = match Invoice::draft(project, &summary, options.range, tax_percentage) {
+        Ok(invoice) => invoice,
+        Err(err) => {
+            utils::error_msg("No Rate Found", &err);
+            std::process::exit(1);
+        }
+    };
+
+    if invoice.lines.is_empty() {
+        utils::error_msg(
+            "Nothing To Invoice",
+            &format!("Nothing was logged for {}.", options.range.describe()),
+        );
+        std::process::exit(1);
+    }
+
+    let format = matches.value_of("format").unwrap_or("markdown");
+    let path = matches.value_of("output");
+
+    // Open the file before taking a number, a number is of no use when the
+    // invoice can't be written
+    let file = match path {
+        Some(path) => match File::create(path) {
+            Ok(file) => Some(file),
+            Err(err) => {
+                utils::error_msg(
+                    "Could Not Write Invoice",
+                    &format!("Unable to create {}: {}", path, err),
+                );
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    // Write to the given file, or to the terminal
+    let write = move |invoice: &Invoice| -> io::Result<()> {
+        let mut buffer: Vec<u8> = Vec::new();
+        invoice::render(invoice, format, &mut buffer)?;
+
+        match file {
+            Some(mut file) => file.write_all(&buffer),
+            None => io::stdout().lock().write_all(&buffer),
+        }
+    };
+
+    // Drafts are for checking, only an issued invoice takes up a number
+    let result = if matches.is_present("draft") {
+        write(&invoice).map_err(|err| format!("Unable to write the invoice: {}", err))
+    } else {
+        invoice.issue(project, write).map(|_| ())
+    };
+
+    if let Err(err) = result {
+        utils::error_msg("Could Not Write Invoice", &err);
+        std::process::exit(1);
+    }
+
+    if let Some(path) = path {
+        utils::info_msg_compact("Written to:", path);
+    }
+
+    Ok(state)
+}
@@ -1,3 +1,6 @@
+use crate::config;
+use crate::dates;
+use crate::duration;
 use crate::ignore::{get_ignore_flag, set_ignore_flag};
 use crate::project::Project;
 use crate::state::State;
@@ -23,32 +26,116 @@ pub fn exec<'a>(
         std::process::exit(0)
     }
 
-    // Let the user either create a new task, or select an existing one
-    let task = match state.task {
-        None => create_or_select_task(&state.project.as_ref().unwrap()),
-        Some(task) => Some(task),
+    // Forgot to log on friday? Then the entry can be backdated
+    let inserted_at = if matches.is_present("date") || matches.is_present("at") {
+        match dates::parse_backdate(matches.value_of("date"), matches.value_of("at")) {
+            Ok(datetime) => Some(datetime),
+            Err(err) => {
+                utils::error_msg("Invalid Date", &err);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+
+    // The time spent can be given upfront instead of through the prompt
+    let time = match matches.value_of("time").map(duration::parse_minutes) {
+        Some(Err(err)) => {
+            utils::error_msg("Invalid Time", &err);
+            std::process::exit(1);
+        }
+        Some(Ok(minutes)) => Some(minutes),
+        None => None,
+    };
+
+    // Without a terminal there is nobody to answer our prompts, so make
+    // sure everything we need was passed along before changing anything.
+    let yes = matches.is_present("yes");
+    if !utils::is_interactive() {
+        ensure_no_prompts_needed(&state, &matches);
+    }
+
+    // Use the task that was asked for, otherwise let the user either
+    // create a new task, or select an existing one
+    let project = state.project.as_ref().unwrap();
+    let task = if let Some(name_or_id) = matches.value_of("task") {
+        Some(find_task(project, name_or_id, state.task.is_none()))
+    } else if let Some(name) = matches.value_of("create-task") {
+        match project.find_task_by_name(name) {
+            Some(task) => Some(task),
+            None => project.add_task(name.to_string()),
+        }
+    } else {
+        match state.task {
+            None => create_or_select_task(project),
+            Some(task) => Some(task),
+        }
     };
 
     utils::info_msg_compact("Task:", &task.as_ref().unwrap().name);
 
+    if let Some(datetime) = &inserted_at {
+        let date = utils::utc_to_local(datetime);
+        utils::info_msg_compact("Date:", &date.format("%A %-e %B, %Y %H:%M").to_string());
+    }
+
     // Either use the last commit message, or prompt the user for a message
-    let last_commit = get_last_commit_message();
-    let message = if matches.is_present("commit") {
+    let message = if matches.is_present("commit") || (yes && !matches.is_present("message")) {
+        let last_commit = get_last_commit_message();
         utils::info_msg_compact("Message:", &last_commit);
         last_commit
     } else if let Some(message) = matches.value_of("message") {
         utils::info_msg_compact("Message:", &message);
         message.to_string()
     } else {
-        prompt_message(last_commit, "".to_string())
+        prompt_message(get_last_commit_message(), "".to_string())
+    };
+
+    // Let's ask the user how many minutes they spent on this task, for
+    // commits we can make an educated guess based on the git history, and
+    // --yes takes the last commit as well
+    let suggestion = if matches.is_present("commit") || yes {
+        task.as_ref().and_then(suggest_minutes)
+    } else {
+        None
     };
 
-    // Let's ask the user how many minutes they spent on this task
-    let minutes = prompt_minutes();
+    // With --yes the suggestion is good enough, no need to ask
+    let accepted = if yes { suggestion } else { None };
+    let minutes = match time.or(accepted) {
+        Some(minutes) => {
+            utils::info_msg_compact("Time:", &utils::format_time(minutes));
+            minutes
+        }
+        None => {
+            if !utils::is_interactive() {
+                missing_input_error(&["--time <duration>"]);
+            }
+
+            prompt_minutes(suggestion)
+        }
+    };
 
     // Write this log entry to the database and touch the task
     if let Some(task) = &task {
-        task.add_log(minutes, message);
+        // The entry is billable unless its task says otherwise, or we do
+        let billable = if matches.is_present("non-billable") {
+            Some(false)
+        } else {
+            None
+        };
+
+        let task_log_id = match &inserted_at {
+            Some(datetime) => task.add_log_at(minutes, message, datetime, billable),
+            None => task.add_log(minutes, message, billable),
+        };
+
+        if task_log_id.is_some() && billable.is_some() {
+            utils::info_msg_compact("Billable:", "no");
+        }
+
+        display_budget(task);
         task.touch()
     }
 
@@ -62,7 +149,72 @@ pub fn exec<'a>(
     Ok(state)
 }
 
-fn create_or_select_task(project: &Project) -> Option<Task> {
+// How the time spent so far compares to the estimate, if the task has one
+pub fn display_budget(task: &Task) {
+    if let Some(estimate) = task.estimate {
+        let budget = utils::format_budget(task.logged_minutes(), estimate);
+        utils::info_msg_compact("Budget:", &budget);
+    }
+}
+
+// Check the arguments against what we would otherwise have to prompt for
+fn ensure_no_prompts_needed(state: &State, matches: &ArgMatches) {
+    let mut missing: Vec<&str> = Vec::new();
+
+    if state.task.is_none() && !matches.is_present("task") && !matches.is_present("create-task") {
+        missing.push("--task <name|id> or --create-task <name>");
+    }
+
+    if !matches.is_present("commit") && !matches.is_present("message") && !matches.is_present("yes")
+    {
+        missing.push("--message <message> (or --commit to use the last commit message)");
+    }
+
+    // With --yes the suggested time is accepted without asking
+    if !matches.is_present("time") && !matches.is_present("yes") {
+        missing.push("--time <duration>");
+    }
+
+    if !missing.is_empty() {
+        missing_input_error(&missing);
+    }
+}
+
+fn missing_input_error(missing: &[&str]) -> ! {
+    utils::error_msg(
+        "No Terminal Found",
+        &format!(
+            "Timecop can't prompt for input because stdin is not a terminal, instead\r\npass the following argument(s):\r\n\r\n  {}",
+            missing.join("\r\n  ")
+        ),
+    );
+    std::process::exit(1);
+}
+
+// Find the task by name or id, when the branch has no task yet it is linked
+// to this one, just like picking an existing task from the list would.
+fn find_task(project: &Project, name_or_id: &str, link_branch: bool) -> Task {
+    match project.find_task(name_or_id) {
+        Some(task) => {
+            if link_branch {
+                task.set_context(project);
+            }
+            task
+        }
+        None => {
+            utils::error_msg(
+                "No Task Found",
+                &format!(
+                    "This project has no task named `{}`, to create it use: --create-task \"{}\"",
+                    name_or_id, name_or_id
+                ),
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+pub fn create_or_select_task(project: &Project) -> Option<Task> {
     let theme = ColorfulTheme::default();
     let tasks = project.list_tasks();
     let mut options: Vec<&str> = Vec::new();
@@ -77,12 +229,22 @@ fn create_or_select_task(project: &Project) -> Option<Task> {
     }
 
     options.push("Nothing, thanks timecop!");
-    let ignore_command = format!("Ignore this branch ({})", branch);
-    options.push(&ignore_command);
+
+    // Without a branch the task can't be remembered, or the branch ignored
+    let ignore_command = branch
+        .as_ref()
+        .map(|b| format!("Ignore this branch ({})", b));
+    if let Some(command) = &ignore_command {
+        options.push(command);
+    }
 
     utils::info_msg(
         "No Task Found",
-        "This branch does not appear to have a task created for it yet.",
+        if branch.is_some() {
+            "This branch does not appear to have a task created for it yet."
+        } else {
+            "You are not on a branch, so the task won't be remembered for next time."
+        },
     );
 
     match Select::with_theme(&theme)
@@ -92,23 +254,13 @@ fn create_or_select_task(project: &Project) -> Option<Task> {
         .paged(true)
         .interact()
     {
-        Ok(0) => create_task(&project),
-        Ok(1) => {
-            if options.len() == 4 {
-                select_tasks(&project, tasks)
-            } else {
-                std::process::exit(0)
-            }
-        }
-        Ok(n) => {
-            if (options.len() == 4 && n != 3) || (options.len() == 3 && n != 2) {
-                std::process::exit(0)
-            }
-
+        Ok(n) if ignore_command.as_deref() == Some(options[n]) => {
             set_ignore_flag();
             std::process::exit(0)
         }
-        Err(_) => std::process::exit(0),
+        Ok(0) => create_task(&project),
+        Ok(1) if !tasks.is_empty() => select_tasks(&project, tasks),
+        _ => std::process::exit(0),
     }
 }
 
@@ -143,20 +295,18 @@ fn select_tasks(project: &Project, tasks: Vec<Task>) -> Option<Task> {
     }
 }
 
-fn get_last_commit_message() -> String {
+// The commit HEAD points at, which works just as well on a detached HEAD
+pub fn get_last_commit_message() -> String {
     let repo = utils::get_current_repo().unwrap();
-    let branch = repo.head().unwrap().shorthand().unwrap().to_string();
-    let object = repo.revparse_single(&branch).unwrap();
-    let commit = object.peel_to_commit().unwrap();
-    return commit
-        .message()
-        .map(|m| m.to_string())
-        .unwrap()
-        .trim()
-        .to_string();
+    let commit = match repo.head().and_then(|head| head.peel_to_commit()) {
+        Ok(commit) => commit,
+        Err(_) => return String::new(),
+    };
+
+    commit.message().unwrap_or("").trim().to_string()
 }
 
-fn prompt_message(default: String, initial: String) -> String {
+pub fn prompt_message(default: String, initial: String) -> String {
     let theme = ColorfulTheme::default();
 
     let message = Input::with_theme(&theme)
@@ -178,15 +328,41 @@ fn prompt_message(default: String, initial: String) -> String {
     }
 }
 
-fn prompt_minutes() -> usize {
-    Input::with_theme(&ColorfulTheme::default())
-        .with_prompt("Estimated time spent? (in minutes)")
-        .interact()
-        .unwrap()
+// Keeps asking until the answer is a valid duration, like 90, 1h30 or 1.5h
+fn prompt_minutes(suggestion: Option<usize>) -> usize {
+    let theme = ColorfulTheme::default();
+    let mut input = Input::<String>::with_theme(&theme);
+    input
+        .with_prompt("Estimated time spent? (90, 1h30, 1.5h)")
+        .validate_with(|input: &String| duration::parse_minutes(input).map(|_| ()));
+
+    if let Some(minutes) = suggestion {
+        input.default(utils::format_time(minutes));
+    }
+
+    duration::parse_minutes(&input.interact().unwrap()).unwrap()
 }
 
-fn get_branch() -> String {
-    let repo = utils::get_current_repo().unwrap();
-    let (_remote, branch) = utils::get_repo_remote_and_branch(repo).unwrap();
+// The gap between this commit and the previous one is a decent estimate,
+// unless it is longer than a session break, in which case the time since
+// the last log entry for this task is used instead. Both are capped at
+// the session break, so a weekend doesn't turn into a 60 hour suggestion.
+fn suggest_minutes(task: &Task) -> Option<usize> {
+    let session_break = config::session_break_minutes();
+    let commit_gap = utils::get_last_commit_gap_minutes();
+
+    let minutes = match commit_gap {
+        Some(gap) if gap <= session_break => Some(gap),
+        _ => task.minutes_since_last_log().or(commit_gap),
+    };
+
+    minutes
+        .filter(|minutes| *minutes > 0)
+        .map(|minutes| minutes.min(session_break))
+}
+
+fn get_branch() -> Option<String> {
+    let repo = utils::get_context_repo()?;
+    let (_remote, branch) = utils::get_repo_remote_and_branch(repo)?;
     branch
 }
@@ -1,11 +1,17 @@
+use crate::dates::{self, DateRange, Zone};
+use crate::duration;
+use crate::render::budget::{BudgetRenderer, TaskEstimate};
+use crate::render::template::TemplateRenderer;
+use crate::render::{self, RenderOptions, ReportRenderer, Rollup};
 use crate::state::State;
-use crate::summary::Summary;
+use crate::summary::{Rounding, RoundingMode, RoundingScope, SummaryOptions};
 use crate::utils;
 
+use chrono::{Datelike, Duration};
 use clap::ArgMatches;
 use std::error;
-use std::io;
-use termion::{color, style};
+use std::fs;
+use std::io::{self, Write};
 
 pub fn exec<'a>(
     state: State<'a>,
@@ -17,111 +23,250 @@ pub fn exec<'a>(
         std::process::exit(1);
     }
 
-    // Get the summary
-    let project = &state.project.as_ref().unwrap();
-    let summary = project.summary();
-    let detailed = matches.is_present("detailed");
+    let options = summary_options(&matches);
+    let project = state.project.as_ref().unwrap();
+
+    // The budget is a view of its own, in text or CSV
+    let renderer = if matches.is_present("budget") {
+        let format = if matches.is_present("csv") {
+            "csv"
+        } else {
+            matches.value_of("format").unwrap_or("text")
+        };
+
+        let csv = match format {
+            "text" => false,
+            "csv" => true,
+            format => {
+                utils::error_msg(
+                    "Unsupported Format",
+                    &format!(
+                        "The budget can only be shown as text or csv, not {}.",
+                        format
+                    ),
+                );
+                std::process::exit(1);
+            }
+        };
 
-    if matches.is_present("csv") {
-        display_csv(summary, detailed)
+        Box::new(BudgetRenderer {
+            csv,
+            estimates: project
+                .list_tasks()
+                .into_iter()
+                .filter_map(|task| {
+                    Some(TaskEstimate {
+                        id: task.id,
+                        minutes: task.estimate?,
+                        name: task.name,
+                    })
+                })
+                .collect(),
+        })
     } else {
-        display_summary(summary, detailed)
+        renderer(&matches)
+    };
+
+    let by = Rollup::parse(matches.value_of("by").unwrap_or("day")).unwrap_or(Rollup::Day);
+    if !renderer.supports(by) {
+        utils::error_msg(
+            "Unsupported Format",
+            &format!(
+                "The {} output can only show days, not a {}.",
+                renderer.name(),
+                by.name()
+            ),
+        );
+        std::process::exit(1);
     }
 
+    // Get the summary
+    let summary = project.summary(&options);
+    let path = matches.value_of("output");
+
+    let render_options = RenderOptions {
+        detailed: matches.is_present("detailed"),
+        range: options.range,
+        rounding: options.rounding,
+        by,
+        week_start: dates::week_start(),
+        color: path.is_none(),
+    };
+
+    write_output(path, |out| renderer.render(&summary, &render_options, out));
+
     Ok(state)
 }
 
-fn display_summary(summary: Summary, detailed: bool) {
-    utils::info_msg_compact("Project Summary:", &summary.name);
-    println!();
-
-    for day in summary.days {
-        let day_name = format!("{}", day.date.format("%A"));
-        let date = format!("({})", day.date.format("%-e %B, %Y"));
-        utils::info_msg_compact(&day_name, &date);
-
-        for task in day.tasks {
-            if detailed {
-                println!(
-                    "  {}{}{}{}",
-                    color::Fg(color::LightWhite),
-                    style::Bold,
-                    task.name,
-                    style::Reset,
-                );
+// The renderer for the format (or template) asked for, shared by the
+// commands showing a summary.
+pub fn renderer(matches: &ArgMatches) -> Box<dyn ReportRenderer> {
+    let format = if matches.is_present("csv") {
+        "csv"
+    } else {
+        matches.value_of("format").unwrap_or("text")
+    };
 
-                for entry in task.entries {
-                    let time = format_time(entry.minutes);
-                    println!(
-                        "    [{}{}{}] {}{}",
-                        color::Fg(color::LightWhite),
-                        time,
-                        style::Reset,
-                        entry.name,
-                        style::Reset,
-                    );
-                }
-            } else {
-                let time = format_time(task.minutes);
-                println!(
-                    "  [{}{}{}] {}",
-                    color::Fg(color::LightWhite),
-                    time,
-                    style::Reset,
-                    task.name,
-                );
+    let renderer = match matches.value_of("template") {
+        Some(template) => match TemplateRenderer::load(template) {
+            Ok(renderer) => Some(Box::new(renderer) as Box<dyn ReportRenderer>),
+            Err(err) => {
+                utils::error_msg("Template Not Found", &err);
+                std::process::exit(1);
             }
+        },
+        None => render::find(format),
+    };
+
+    match renderer {
+        Some(renderer) => renderer,
+        None => {
+            utils::error_msg(
+                "Unsupported Format",
+                &format!(
+                    "There is no `{}` format, try one of: {}.",
+                    format,
+                    render::names().join(", ")
+                ),
+            );
+            std::process::exit(1);
         }
-        println!();
     }
 }
 
-fn display_csv(summary: Summary, detailed: bool) {
-    // Create CSV writer to STDOUT
-    let mut wtr = csv::Writer::from_writer(io::stdout());
-    let mut headers: Vec<&str> = vec!["Project", "Date", "Time Spent (Minutes)", "Task"];
+// Write to the given file, or to the terminal. Everything is rendered
+// before the file is touched, so a failure doesn't leave half a report.
+pub fn write_output<F>(path: Option<&str>, render: F)
+where
+    F: FnOnce(&mut dyn io::Write) -> io::Result<()>,
+{
+    let mut buffer: Vec<u8> = Vec::new();
 
-    if detailed {
-        headers.push("Log Entry");
+    let result = render(&mut buffer).and_then(|_| match path {
+        Some(path) => fs::write(path, &buffer).map_err(|err| {
+            io::Error::new(err.kind(), format!("Unable to write {}: {}", path, err))
+        }),
+        None => io::stdout().lock().write_all(&buffer),
+    });
+
+    if let Err(err) = result {
+        utils::error_msg("Could Not Write Report", &err.to_string());
+        std::process::exit(1);
     }
 
-    // Write our headers first
-    wtr.write_record(&headers).unwrap();
-
-    // Write all the records
-    for day in summary.days {
-        let date = format!("{}", day.date.format("%Y-%m-%d"));
-
-        for task in day.tasks {
-            if detailed {
-                for entry in task.entries {
-                    wtr.write_record(&[
-                        &summary.name,
-                        &date,
-                        &format!("{}", entry.minutes),
-                        &task.name,
-                        &entry.name,
-                    ])
-                    .unwrap();
-                }
-            } else {
-                wtr.write_record(&[
-                    &summary.name,
-                    &date,
-                    &format!("{}", task.minutes),
-                    &task.name,
-                ])
-                .unwrap();
-            }
+    if let Some(path) = path {
+        utils::info_msg_compact("Written to:", path);
+    }
+}
+
+// Work out which days to summarize and how, from the arguments that are
+// shared by the commands showing a summary.
+pub fn summary_options(matches: &ArgMatches) -> SummaryOptions {
+    // Days are grouped in the timezone given, or the configured one
+    let zone = match matches.value_of("tz") {
+        Some(tz) => Zone::parse(tz),
+        None => Zone::from_config(),
+    };
+
+    let zone = match zone {
+        Ok(zone) => zone,
+        Err(err) => {
+            utils::error_msg("Invalid Timezone", &err);
+            std::process::exit(1);
         }
+    };
+
+    let range = match parse_range(matches, &zone) {
+        Ok(range) => range,
+        Err(err) => {
+            utils::error_msg("Invalid Date", &err);
+            std::process::exit(1);
+        }
+    };
+
+    let rounding = match matches.value_of("round") {
+        Some(round) => match parse_rounding(matches, round) {
+            Ok(rounding) => Some(rounding),
+            Err(err) => {
+                utils::error_msg("Invalid Rounding", &err);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    SummaryOptions {
+        zone,
+        range,
+        rounding,
+        billable_only: matches.is_present("billable-only"),
     }
+}
+
+fn parse_rounding(matches: &ArgMatches, round: &str) -> Result<Rounding, String> {
+    let minutes = duration::parse_minutes(round)?;
+
+    let mode = match matches.value_of("round-mode") {
+        Some("up") => RoundingMode::Up,
+        Some("down") => RoundingMode::Down,
+        _ => RoundingMode::Nearest,
+    };
 
-    // output csv
-    wtr.flush().unwrap();
+    let scope = match matches.value_of("round-per") {
+        Some("entry") => RoundingScope::Entry,
+        Some("day") => RoundingScope::Day,
+        _ => RoundingScope::Task,
+    };
+
+    Ok(Rounding {
+        minutes,
+        mode,
+        scope,
+    })
 }
 
-fn format_time(time: usize) -> String {
-    let minutes = time % 60;
-    let hours = time / 60;
-    format!("{:02}h{:02}m", hours, minutes)
+// The days to include, relative to today in the zone the days are grouped in
+fn parse_range(matches: &ArgMatches, zone: &Zone) -> Result<DateRange, String> {
+    let today = zone.today();
+    let week_start = dates::week_start();
+
+    let range = if matches.is_present("today") {
+        DateRange::day(today)
+    } else if matches.is_present("yesterday") {
+        DateRange::day(today - Duration::days(1))
+    } else if matches.is_present("this-week") {
+        DateRange::week_of(today, week_start)
+    } else if matches.is_present("last-week") {
+        DateRange::week_of(today - Duration::days(7), week_start)
+    } else if matches.is_present("this-month") {
+        DateRange::month_of(today)
+    } else if matches.is_present("last-month") {
+        DateRange::month_of(today.with_day(1).unwrap() - Duration::days(1))
+    } else {
+        let parse = |name: &str| match matches.value_of(name) {
+            Some(date) => dates::parse_date(date, today).map(Some),
+            None => Ok(None),
+        };
+
+        DateRange {
+            from: parse("from")?,
+            to: parse("to")?,
+        }
+    };
+
+    if let DateRange {
+        from: Some(from),
+        to: Some(to),
+    } = range
+    {
+        if from > to {
+            return Err(format!(
+                "The range starts ({}) after it ends ({})",
+                from.format("%Y-%m-%d"),
+                to.format("%Y-%m-%d")
+            ));
+        }
+    }
+
+    Ok(range)
 }
@@ -0,0 +1,37 @@
+use crate::state::State;
+use crate::timer::Timer;
+use crate::utils;
+
+use clap::ArgMatches;
+use std::error;
+
+pub fn exec<'a>(
+    state: State<'a>,
+    _matches: ArgMatches<'a>,
+) -> Result<State<'a>, Box<dyn error::Error>> {
+    // Make sure there is a timer to pause
+    let timer = match Timer::find_running() {
+        Some(timer) => timer,
+        None => {
+            utils::error_msg(
+                "No Timer Running",
+                "There is no running timer to pause, start one first: $ timecop start",
+            );
+            std::process::exit(1);
+        }
+    };
+
+    if timer.is_paused() {
+        println!("The timer is already paused, continue with: $ timecop resume");
+        return Ok(state);
+    }
+
+    // Close the active interval, resuming will start a new one
+    timer.pause();
+
+    let name = timer.task().map(|t| t.name).unwrap_or_default();
+    utils::info_msg_compact("Timer paused:", &name);
+    utils::info_msg_compact("Time:", &utils::format_time(timer.elapsed_minutes()));
+
+    Ok(state)
+}
@@ -0,0 +1,103 @@
+use crate::invoice;
+use crate::project::Project;
+use crate::state::State;
+use crate::task::Task;
+use crate::utils;
+
+use clap::ArgMatches;
+use std::error;
+
+pub fn exec<'a>(
+    state: State<'a>,
+    matches: ArgMatches<'a>,
+) -> Result<State<'a>, Box<dyn error::Error>> {
+    // Make sure we have an active project
+    if state.project.is_none() {
+        utils::error_msg("No Project Found", "Timecop requires a project to be defined before you can start\r\nusing timecop to log entries, first run: $ timecop init");
+        std::process::exit(1);
+    }
+
+    let project = state.project.as_ref().unwrap();
+
+    let rate = match matches.value_of("amount") {
+        Some(amount) => match invoice::parse_amount(amount) {
+            Ok(rate) => Some(rate),
+            Err(err) => {
+                utils::error_msg("Invalid Rate", &err);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    let clear = matches.is_present("clear");
+    let currency = matches
+        .value_of("currency")
+        .map(|currency| currency.trim().to_uppercase());
+
+    match matches.value_of("task") {
+        Some(name) => {
+            let task = match project.find_task(name) {
+                Some(task) => task,
+                None => {
+                    utils::error_msg(
+                        "No Task Found",
+                        &format!("There is no task named `{}` in this project.", name),
+                    );
+                    std::process::exit(1);
+                }
+            };
+
+            if rate.is_some() || clear {
+                task.set_rate(rate);
+            }
+
+            let task = Task::get_by_id(task.id).unwrap();
+            display_task_rate(project, &task);
+        }
+        None => {
+            if rate.is_some() || currency.is_some() || clear {
+                let rate = if clear { None } else { rate.or(project.rate) };
+                let currency = if clear {
+                    None
+                } else {
+                    currency.or_else(|| project.currency.clone())
+                };
+                project.set_rate(rate, currency);
+            }
+
+            let project = Project::get_by_id(project.id).unwrap();
+            display_rates(&project);
+        }
+    }
+
+    Ok(state)
+}
+
+// The rate of the project, followed by the tasks that have one of their own
+fn display_rates(project: &Project) {
+    utils::info_msg_compact("Rate:", &format_rate(project, project.rate));
+
+    for task in project.list_tasks().iter().filter(|t| t.rate.is_some()) {
+        println!("  {} {}", task.name, format_rate(project, task.rate));
+    }
+}
+
+fn display_task_rate(project: &Project, task: &Task) {
+    let rate = match task.rate {
+        Some(_) => format_rate(project, task.rate),
+        None => format!("{} (from the project)", format_rate(project, project.rate)),
+    };
+
+    utils::info_msg_compact(&format!("{}:", task.name), &rate);
+}
+
+fn format_rate(project: &Project, rate: Option<i64>) -> String {
+    match (rate, &project.currency) {
+        (Some(rate), Some(currency)) => {
+            format!("{} {} per hour", invoice::format_amount(rate), currency)
+        }
+        (Some(rate), None) => format!("{} per hour", invoice::format_amount(rate)),
+        (None, _) => "none, set one with `timecop rate <amount>`".to_string(),
+    }
+}
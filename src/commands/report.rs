@@ -0,0 +1,60 @@
+use crate::dates;
+use crate::project::Project;
+use crate::render::{RenderOptions, Rollup};
+use crate::state::State;
+use crate::summary;
+use crate::utils;
+
+use clap::ArgMatches;
+use std::error;
+
+pub fn exec<'a>(
+    state: State<'a>,
+    matches: ArgMatches<'a>,
+) -> Result<State<'a>, Box<dyn error::Error>> {
+    // Find the projects to report on, the current one by default
+    let projects: Vec<Project> = if matches.is_present("all-projects") {
+        Project::list()
+    } else if let Some(name) = matches.value_of("project") {
+        match Project::find(name) {
+            Some(project) => vec![project],
+            None => {
+                utils::error_msg(
+                    "No Project Found",
+                    &format!("There is no project named `{}`.", name),
+                );
+                std::process::exit(1);
+            }
+        }
+    } else {
+        match &state.project {
+            Some(project) => vec![project.clone()],
+            None => {
+                utils::error_msg("No Project Found", "There is no project for the current directory, use `--project <name>`\r\nto pick one or `--all-projects` to report on every project.");
+                std::process::exit(1);
+            }
+        }
+    };
+
+    let options = super::output::summary_options(&matches);
+    let renderer = super::output::renderer(&matches);
+
+    let summary = summary::for_projects(&projects, &options);
+    let path = matches.value_of("output");
+
+    // The projects are combined by day, so there is nothing to roll up
+    let render_options = RenderOptions {
+        detailed: matches.is_present("detailed"),
+        range: options.range,
+        rounding: options.rounding,
+        by: Rollup::Day,
+        week_start: dates::week_start(),
+        color: path.is_none(),
+    };
+
+    super::output::write_output(path, |out| {
+        renderer.render_combined(&summary, &render_options, out)
+    });
+
+    Ok(state)
+}
@@ -0,0 +1,79 @@
+use crate::commands::{log, stop};
+use crate::state::State;
+use crate::timer::Timer;
+use crate::utils;
+
+use clap::ArgMatches;
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use std::error;
+
+pub fn exec<'a>(
+    mut state: State<'a>,
+    _matches: ArgMatches<'a>,
+) -> Result<State<'a>, Box<dyn error::Error>> {
+    // Make sure we have an active project
+    if state.project.is_none() {
+        utils::error_msg("No Project Found", "Timecop requires a project to be defined before you can start\r\nusing timecop to log entries, first run: $ timecop init");
+        std::process::exit(1);
+    }
+
+    // Let the user either create a new task, or select an existing one
+    let task = match state.task {
+        None => log::create_or_select_task(state.project.as_ref().unwrap()),
+        Some(task) => Some(task),
+    };
+
+    let task = match task {
+        None => std::process::exit(0),
+        Some(task) => task,
+    };
+
+    // Only one timer is allowed to run at a time
+    if let Some(timer) = Timer::find_running() {
+        if timer.task_id == task.id {
+            if timer.is_paused() {
+                timer.resume();
+                utils::info_msg_compact("Timer resumed:", &task.name);
+            } else {
+                utils::info_msg_compact("Timer already running:", &task.name);
+            }
+
+            state.task = Some(task);
+            return Ok(state);
+        }
+
+        if !prompt_stop_running_timer(&timer) {
+            std::process::exit(0);
+        }
+
+        stop::stop_timer(timer, None);
+    }
+
+    // Start the timer and touch the task
+    if task.start_timer().is_none() {
+        return Err("Failed to start the timer".into());
+    }
+
+    task.touch();
+    utils::info_msg_compact("Timer started:", &task.name);
+
+    // Assign task to state
+    state.task = Some(task);
+    Ok(state)
+}
+
+fn prompt_stop_running_timer(timer: &Timer) -> bool {
+    let name = match timer.task() {
+        Some(task) => task.name,
+        None => "an unknown task".to_string(),
+    };
+
+    Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!(
+            "A timer is already running for {} ({}), do you want to stop it?",
+            name,
+            utils::format_time(timer.elapsed_minutes())
+        ))
+        .interact()
+        .unwrap()
+}
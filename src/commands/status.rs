@@ -0,0 +1,47 @@
+use crate::state::State;
+use crate::timer::Timer;
+use crate::utils;
+
+use chrono::{Local, TimeZone};
+use clap::ArgMatches;
+use std::error;
+
+pub fn exec<'a>(
+    state: State<'a>,
+    _matches: ArgMatches<'a>,
+) -> Result<State<'a>, Box<dyn error::Error>> {
+    let timer = match Timer::find_running() {
+        Some(timer) => timer,
+        None => {
+            println!("No timer running.");
+            return Ok(state);
+        }
+    };
+
+    let name = match timer.task() {
+        Some(task) => task.name,
+        None => "Unknown task".to_string(),
+    };
+
+    let started_at = Local.from_utc_datetime(&timer.started_at);
+    let intervals = timer.intervals().len();
+
+    if timer.is_paused() {
+        utils::info_msg_compact("Timer paused:", &name);
+    } else {
+        utils::info_msg_compact("Timer running:", &name);
+    }
+
+    utils::info_msg_compact(
+        "Time:",
+        &format!(
+            "{} (started at {}, {} interval{})",
+            utils::format_time(timer.elapsed_minutes()),
+            started_at.format("%H:%M"),
+            intervals,
+            if intervals == 1 { "" } else { "s" }
+        ),
+    );
+
+    Ok(state)
+}
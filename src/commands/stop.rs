@@ -0,0 +1,79 @@
+use crate::commands::log;
+use crate::state::State;
+use crate::timer::Timer;
+use crate::utils;
+
+use chrono::Utc;
+use clap::ArgMatches;
+use std::error;
+
+pub fn exec<'a>(
+    state: State<'a>,
+    matches: ArgMatches<'a>,
+) -> Result<State<'a>, Box<dyn error::Error>> {
+    // Make sure there is a timer to stop
+    let timer = match Timer::find_running() {
+        Some(timer) => timer,
+        None => {
+            utils::error_msg(
+                "No Timer Running",
+                "There is no running timer to stop, start one first: $ timecop start",
+            );
+            std::process::exit(1);
+        }
+    };
+
+    // The entry is billable unless its task says otherwise, or we do
+    let billable = if matches.is_present("non-billable") {
+        Some(false)
+    } else {
+        None
+    };
+
+    stop_timer(timer, billable);
+    Ok(state)
+}
+
+// Stop the given timer and turn the measured time into a log entry for
+// the task it was started for.
+pub fn stop_timer(timer: Timer, billable: Option<bool>) {
+    // The time spent typing doesn't count, but the timer keeps running until
+    // there is a message so aborting the prompt doesn't lose anything
+    let stopped_at = Utc::now().naive_utc();
+    let minutes = timer.elapsed_minutes();
+
+    let task = match timer.task() {
+        Some(task) => task,
+        None => {
+            timer.stop();
+            return;
+        }
+    };
+
+    utils::info_msg_compact("Task:", &task.name);
+    utils::info_msg_compact("Time:", &utils::format_time(minutes));
+
+    // Nothing worth logging, so just throw the timer away
+    if minutes == 0 {
+        timer.stop();
+        println!("The timer ran for less than a minute, nothing was logged.");
+        return;
+    }
+
+    // Ask the user what they worked on, the time we already know
+    let message = log::prompt_message(log::get_last_commit_message(), "".to_string());
+    timer.pause_at(stopped_at);
+
+    // Write this log entry (and its intervals) to the database and touch the task
+    if let Some(task_log_id) = task.add_log(minutes, message, billable) {
+        timer.attach_to_log(task_log_id);
+
+        if billable.is_some() {
+            utils::info_msg_compact("Billable:", "no");
+        }
+    }
+
+    log::display_budget(&task);
+    task.touch();
+    timer.stop();
+}
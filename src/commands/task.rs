@@ -0,0 +1,51 @@
+mod estimate;
+
+use crate::project::Project;
+use crate::state::State;
+use crate::task::Task;
+use crate::utils;
+
+use clap::ArgMatches;
+use std::error;
+
+pub fn exec<'a>(
+    state: State<'a>,
+    matches: ArgMatches<'a>,
+) -> Result<State<'a>, Box<dyn error::Error>> {
+    // Make sure we have an active project
+    if state.project.is_none() {
+        utils::error_msg("No Project Found", "Timecop requires a project to be defined before you can start\r\nusing timecop to log entries, first run: $ timecop init");
+        std::process::exit(1);
+    }
+
+    let subcommand = super::subcommand_name(&matches)?;
+    let submatches = super::subcommand_matches(&matches, &subcommand)?;
+
+    if subcommand == "estimate" {
+        estimate::exec(state, submatches)
+    } else {
+        Err("Unknown command".into())
+    }
+}
+
+// The task asked for by name or id, otherwise the task of the current branch
+fn find_task(project: &Project, current: Option<&Task>, name_or_id: Option<&str>) -> Task {
+    let task = match name_or_id {
+        Some(name_or_id) => project.find_task(name_or_id),
+        None => current.and_then(|task| Task::get_by_id(task.id)),
+    };
+
+    match task {
+        Some(task) => task,
+        None => {
+            let message = match name_or_id {
+                Some(name_or_id) => format!("This project has no task named `{}`.", name_or_id),
+                None => "There is no task for the current branch, pick one with --task <name|id>."
+                    .to_string(),
+            };
+
+            utils::error_msg("No Task Found", &message);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use crate::duration;
+use crate::state::State;
+use crate::task::Task;
+use crate::utils;
+
+use clap::ArgMatches;
+use std::error;
+
+pub fn exec<'a>(
+    state: State<'a>,
+    matches: ArgMatches<'a>,
+) -> Result<State<'a>, Box<dyn error::Error>> {
+    let project = state.project.as_ref().unwrap();
+    let task = super::find_task(project, state.task.as_ref(), matches.value_of("task"));
+
+    if let Some(hours) = matches.value_of("hours") {
+        match duration::parse_hours(hours) {
+            Ok(estimate) => task.set_estimate(Some(estimate)),
+            Err(err) => {
+                utils::error_msg("Invalid Estimate", &err);
+                std::process::exit(1);
+            }
+        }
+    } else if matches.is_present("clear") {
+        task.set_estimate(None);
+    }
+
+    let task = Task::get_by_id(task.id).unwrap();
+    utils::info_msg_compact("Task:", &task.name);
+
+    match task.estimate {
+        Some(estimate) => {
+            let budget = utils::format_budget(task.logged_minutes(), estimate);
+            utils::info_msg_compact("Budget:", &budget);
+        }
+        None => utils::info_msg_compact(
+            "Budget:",
+            "no estimate yet, set one with `timecop task estimate <hours>`",
+        ),
+    }
+
+    Ok(state)
+}
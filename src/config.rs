@@ -0,0 +1,38 @@
+use crate::utils;
+
+// Settings live in git config under the `timecop` section, which means they
+// can be set for every repository (`git config --global timecop.sessionBreak 90`)
+// or be overridden for a single repository by leaving out the `--global`.
+fn open_config() -> Option<git2::Config> {
+    let config = match utils::get_current_repo() {
+        Some(repo) => repo.config(),
+        None => git2::Config::open_default(),
+    };
+
+    config.ok().and_then(|mut config| config.snapshot().ok())
+}
+
+pub fn get_string(key: &str) -> Option<String> {
+    open_config().and_then(|config| config.get_string(key).ok())
+}
+
+pub fn get_i64(key: &str) -> Option<i64> {
+    open_config().and_then(|config| config.get_i64(key).ok())
+}
+
+// The maximum number of minutes between two commits before we assume
+// the user took a break in between, defaults to two hours.
+pub fn session_break_minutes() -> usize {
+    match get_i64("timecop.sessionBreak") {
+        Some(minutes) if minutes > 0 => minutes as usize,
+        _ => 120,
+    }
+}
+
+// The tax percentage to add to invoices, unless given with `--tax`
+pub fn tax_percentage() -> f64 {
+    get_string("timecop.tax")
+        .and_then(|tax| tax.trim().trim_end_matches('%').parse::<f64>().ok())
+        .filter(|tax| (0.0..=100.0).contains(tax))
+        .unwrap_or(0.0)
+}
@@ -1,3 +1,5 @@
+use crate::utils;
+
 use std::cell::{Ref, RefCell};
 use std::path::PathBuf;
 
@@ -9,7 +11,8 @@ thread_local!(static DBCONN: RefCell<Option<sqlite::Connection>> = RefCell::new(
 // List of static migration strings which will be executed in order,
 // the database will store the 'last used' index and work it's way up
 // from there next time the app starts. Only migrates upwards.
-static MIGRATIONS: [&str; 1] = ["
+static MIGRATIONS: [&str; 9] = [
+    "
   CREATE TABLE IF NOT EXISTS projects (
     id              INTEGER PRIMARY KEY,
     name            TEXT NOT NULL,
@@ -57,7 +60,109 @@ static MIGRATIONS: [&str; 1] = ["
     inserted_at     DATETIME NOT NULL,
     updated_at      DATETIME NOT NULL
   );
-  "];
+  ",
+    "
+  CREATE TABLE IF NOT EXISTS timers (
+    id              INTEGER PRIMARY KEY,
+    task_id         INTEGER NOT NULL,
+    started_at      DATETIME NOT NULL,
+    inserted_at     DATETIME NOT NULL,
+    updated_at      DATETIME NOT NULL,
+
+    FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE
+  );
+  ",
+    "
+  CREATE TABLE IF NOT EXISTS timer_intervals (
+    id              INTEGER PRIMARY KEY,
+    timer_id        INTEGER NOT NULL,
+    started_at      DATETIME NOT NULL,
+    stopped_at      DATETIME NULL,
+    inserted_at     DATETIME NOT NULL,
+    updated_at      DATETIME NOT NULL,
+
+    FOREIGN KEY (timer_id) REFERENCES timers (id) ON DELETE CASCADE
+  );
+
+  CREATE TABLE IF NOT EXISTS task_log_intervals (
+    id              INTEGER PRIMARY KEY,
+    task_log_id     INTEGER NOT NULL,
+    started_at      DATETIME NOT NULL,
+    stopped_at      DATETIME NOT NULL,
+    inserted_at     DATETIME NOT NULL,
+    updated_at      DATETIME NOT NULL,
+
+    FOREIGN KEY (task_log_id) REFERENCES task_logs (id) ON DELETE CASCADE
+  );
+
+  INSERT INTO timer_intervals (timer_id, started_at, stopped_at, inserted_at, updated_at)
+  SELECT t.id, t.started_at, NULL, t.inserted_at, t.updated_at
+  FROM timers t;
+  ",
+    "
+  ALTER TABLE task_logs ADD COLUMN utc_offset INTEGER NOT NULL DEFAULT 0;
+
+  UPDATE task_logs
+  SET utc_offset = (strftime('%s', inserted_at, 'localtime') - strftime('%s', inserted_at)) / 60;
+  ",
+    "
+  -- Rates and amounts are stored in cents, the rates per hour
+  ALTER TABLE projects ADD COLUMN rate INTEGER NULL;
+  ALTER TABLE projects ADD COLUMN currency TEXT NULL;
+  ALTER TABLE tasks ADD COLUMN rate INTEGER NULL;
+
+  CREATE TABLE IF NOT EXISTS invoices (
+    id              INTEGER PRIMARY KEY,
+    number          INTEGER NOT NULL UNIQUE,
+    project_id      INTEGER NOT NULL,
+    period_from     DATE NULL,
+    period_to       DATE NULL,
+    currency        TEXT NULL,
+    subtotal        INTEGER NOT NULL,
+    tax_percentage  REAL NOT NULL,
+    total           INTEGER NOT NULL,
+    inserted_at     DATETIME NOT NULL,
+    updated_at      DATETIME NOT NULL,
+
+    FOREIGN KEY (project_id) REFERENCES projects (id)
+  );
+  ",
+    "
+  -- Tasks and log entries without a flag of their own inherit it
+  ALTER TABLE projects ADD COLUMN billable INTEGER NOT NULL DEFAULT 1;
+  ALTER TABLE tasks ADD COLUMN billable INTEGER NULL;
+  ALTER TABLE task_logs ADD COLUMN billable INTEGER NULL;
+  ",
+    "
+  -- In minutes
+  ALTER TABLE tasks ADD COLUMN estimate INTEGER NULL;
+  ",
+    "
+  -- The contexts themselves are rewritten by `normalize_contexts`
+  CREATE INDEX IF NOT EXISTS contexts_context ON contexts (context);
+  ",
+    "
+  -- The lines as they were invoiced, so an invoice can always be reproduced
+  CREATE TABLE IF NOT EXISTS invoice_lines (
+    id              INTEGER PRIMARY KEY,
+    invoice_id      INTEGER NOT NULL,
+    task            TEXT NOT NULL,
+    minutes         INTEGER NOT NULL,
+    billable        INTEGER NOT NULL,
+    rate            INTEGER NOT NULL,
+    amount          INTEGER NOT NULL,
+    inserted_at     DATETIME NOT NULL,
+    updated_at      DATETIME NOT NULL,
+
+    FOREIGN KEY (invoice_id) REFERENCES invoices (id) ON DELETE CASCADE
+  );
+  ",
+];
+
+// Migrations that can't be written in SQL alone, these run right after the
+// SQL migration with the same id.
+type CodeMigration = fn(&sqlite::Connection);
+static CODE_MIGRATIONS: [(usize, CodeMigration); 1] = [(7, normalize_contexts)];
 
 // Open the database and store it in our refcell for later use.
 pub fn open_db() {
@@ -165,6 +270,10 @@ fn run_migration(db: &sqlite::Connection, migration_id: usize) {
         migration_id, migration
     );
 
+    // A migration is applied as a whole or not at all, when anything below
+    // panics the transaction is never committed
+    db.execute("BEGIN;").unwrap();
+
     // Attempt to execute the migration
     match db.execute(&migration) {
         Ok(_) => 0,
@@ -173,6 +282,13 @@ fn run_migration(db: &sqlite::Connection, migration_id: usize) {
         }
     };
 
+    // Followed by the part that needs some code, if there is one
+    for (id, migrate) in CODE_MIGRATIONS.iter() {
+        if *id == migration_id {
+            migrate(db);
+        }
+    }
+
     // Insert a new record indicating this migration has been executed.
     let mut statement = db.prepare("INSERT INTO migrations VALUES(?);").unwrap();
     statement.bind(1, migration_id as i64).unwrap();
@@ -180,4 +296,264 @@ fn run_migration(db: &sqlite::Connection, migration_id: usize) {
         Ok(_) => true,
         Err(err) => panic!("Logging the migration failed:\r\n{:?}", err),
     };
+    drop(statement);
+
+    db.execute("COMMIT;").unwrap();
+}
+
+// Contexts used to be stored with the remote exactly as it was configured,
+// rewrite them to the canonical remote (see `utils::normalize_remote`).
+// Projects that turn out to share a remote are merged into the oldest one,
+// just like the tasks of a project that turn out to share a context or a
+// name, and contexts that end up the same are only kept once.
+fn normalize_contexts(db: &sqlite::Connection) {
+    let canonical = |context: &str| match context.find('#') {
+        Some(index) => format!(
+            "{}{}",
+            utils::normalize_remote(&context[..index]),
+            &context[index..]
+        ),
+        None => utils::normalize_remote(context),
+    };
+
+    // Remember where every task started out before projects are merged
+    let origins: Vec<(i64, i64)> = list_tasks(db)
+        .into_iter()
+        .map(|(task_id, project_id, _)| (task_id, project_id))
+        .collect();
+    let origin = |task_id: i64| {
+        origins
+            .iter()
+            .find(|(id, _)| *id == task_id)
+            .map(|(_, project_id)| *project_id)
+    };
+
+    // Find the projects to merge, following earlier merges along the way
+    let mut owners: Vec<(String, i64)> = Vec::new();
+    let mut merged: Vec<(i64, i64)> = Vec::new();
+
+    let mut project_contexts: Vec<(i64, String)> = list_contexts(db)
+        .into_iter()
+        .filter(|(_, _, task_id, _)| task_id.is_none())
+        .map(|(_, project_id, _, context)| (project_id, canonical(&context)))
+        .collect();
+    project_contexts.sort();
+
+    for (project_id, remote) in project_contexts {
+        let project_id = merged
+            .iter()
+            .find(|(from, _)| *from == project_id)
+            .map_or(project_id, |(_, into)| *into);
+
+        match owners.iter().find(|(r, _)| *r == remote) {
+            Some((_, owner)) if *owner != project_id => {
+                merge_projects(db, project_id, *owner);
+                merged.push((project_id, *owner));
+            }
+            Some(_) => {}
+            None => owners.push((remote, project_id)),
+        }
+    }
+
+    // Tasks with the same name that came from two merged projects are the
+    // same task, as are tasks started on the same branch of two spellings of
+    // a remote. Tasks that already shared a project are left alone.
+    let mut task_contexts: Vec<(i64, String)> = list_contexts(db)
+        .into_iter()
+        .filter_map(|(_, _, task_id, context)| task_id.map(|id| (id, canonical(&context))))
+        .collect();
+    let mut kept: Vec<(i64, i64, String)> = Vec::new();
+
+    for (task_id, project_id, name) in list_tasks(db) {
+        let shares_context = |other: i64| {
+            task_contexts
+                .iter()
+                .filter(|(id, _)| *id == task_id)
+                .any(|(_, context)| task_contexts.contains(&(other, context.to_string())))
+        };
+
+        let duplicate = kept
+            .iter()
+            .find(|(other, other_project, other_name)| {
+                *other_project == project_id
+                    && ((*other_name == name && origin(*other) != origin(task_id))
+                        || shares_context(*other))
+            })
+            .map(|(other, _, _)| *other);
+
+        match duplicate {
+            Some(into) => {
+                merge_tasks(db, task_id, into);
+
+                for context in task_contexts.iter_mut().filter(|(id, _)| *id == task_id) {
+                    context.0 = into;
+                }
+            }
+            None => kept.push((task_id, project_id, name)),
+        }
+    }
+
+    // Remove the contexts that are now the same, then rewrite the rest
+    let mut seen: Vec<(i64, Option<i64>, String)> = Vec::new();
+    let mut updates: Vec<(i64, String)> = Vec::new();
+
+    for (id, project_id, task_id, context) in list_contexts(db) {
+        let key = (project_id, task_id, canonical(&context));
+
+        if seen.contains(&key) {
+            execute(
+                db,
+                "DELETE FROM contexts WHERE id = ?;",
+                &[sqlite::Value::Integer(id)],
+            );
+        } else {
+            updates.push((id, key.2.to_string()));
+            seen.push(key);
+        }
+    }
+
+    for (id, context) in updates {
+        execute(
+            db,
+            "UPDATE contexts SET context = ?, updated_at = DATETIME() WHERE id = ?;",
+            &[sqlite::Value::String(context), sqlite::Value::Integer(id)],
+        );
+    }
+
+    // Ignored branches are stored as a context as well
+    let mut cursor = db
+        .prepare("SELECT id, context FROM ignored;")
+        .unwrap()
+        .into_cursor();
+
+    let mut ignored: Vec<(i64, String)> = Vec::new();
+    while let Ok(Some(row)) = cursor.next() {
+        ignored.push((
+            row[0].as_integer().unwrap(),
+            canonical(row[1].as_string().unwrap_or("")),
+        ));
+    }
+
+    for (id, context) in ignored {
+        execute(
+            db,
+            "UPDATE ignored SET context = ?, updated_at = DATETIME() WHERE id = ?;",
+            &[sqlite::Value::String(context), sqlite::Value::Integer(id)],
+        );
+    }
+}
+
+// Every context as (id, project_id, task_id, context), oldest first
+fn list_contexts(db: &sqlite::Connection) -> Vec<(i64, i64, Option<i64>, String)> {
+    let mut cursor = db
+        .prepare("SELECT id, project_id, task_id, context FROM contexts ORDER BY id ASC;")
+        .unwrap()
+        .into_cursor();
+
+    let mut results = Vec::new();
+    while let Ok(Some(row)) = cursor.next() {
+        results.push((
+            row[0].as_integer().unwrap(),
+            row[1].as_integer().unwrap(),
+            row[2].as_integer(),
+            row[3].as_string().unwrap_or("").to_string(),
+        ));
+    }
+
+    results
+}
+
+// Every task as (id, project_id, name), oldest first
+fn list_tasks(db: &sqlite::Connection) -> Vec<(i64, i64, String)> {
+    let mut cursor = db
+        .prepare("SELECT id, project_id, name FROM tasks ORDER BY id ASC;")
+        .unwrap()
+        .into_cursor();
+
+    let mut results = Vec::new();
+    while let Ok(Some(row)) = cursor.next() {
+        results.push((
+            row[0].as_integer().unwrap(),
+            row[1].as_integer().unwrap(),
+            row[2].as_string().unwrap_or("").to_string(),
+        ));
+    }
+
+    results
+}
+
+// Move everything of one project over to another, and remove the first. The
+// settings of the first are kept where the other doesn't have its own, and
+// as billable is only ever turned off on purpose it stays off.
+fn merge_projects(db: &sqlite::Connection, from: i64, into: i64) {
+    let ids = [sqlite::Value::Integer(into), sqlite::Value::Integer(from)];
+
+    execute(
+        db,
+        "
+        UPDATE projects
+        SET rate = COALESCE(rate, (SELECT rate FROM projects WHERE id = ?2)),
+            currency = COALESCE(currency, (SELECT currency FROM projects WHERE id = ?2)),
+            billable = MIN(billable, (SELECT billable FROM projects WHERE id = ?2))
+        WHERE id = ?1;",
+        &ids,
+    );
+
+    execute(
+        db,
+        "UPDATE tasks SET project_id = ? WHERE project_id = ?;",
+        &ids,
+    );
+    execute(
+        db,
+        "UPDATE contexts SET project_id = ? WHERE project_id = ?;",
+        &ids,
+    );
+    execute(
+        db,
+        "UPDATE invoices SET project_id = ? WHERE project_id = ?;",
+        &ids,
+    );
+    execute(db, "DELETE FROM projects WHERE id = ?;", &ids[1..]);
+}
+
+// Move the log entries, timers and contexts of one task over to another, and
+// remove the first. Settings are kept like they are for projects.
+fn merge_tasks(db: &sqlite::Connection, from: i64, into: i64) {
+    let ids = [sqlite::Value::Integer(into), sqlite::Value::Integer(from)];
+
+    execute(
+        db,
+        "
+        UPDATE tasks
+        SET rate = COALESCE(rate, (SELECT rate FROM tasks WHERE id = ?2)),
+            billable = COALESCE(billable, (SELECT billable FROM tasks WHERE id = ?2)),
+            estimate = COALESCE(estimate, (SELECT estimate FROM tasks WHERE id = ?2))
+        WHERE id = ?1;",
+        &ids,
+    );
+    execute(
+        db,
+        "UPDATE task_logs SET task_id = ? WHERE task_id = ?;",
+        &ids,
+    );
+    execute(db, "UPDATE timers SET task_id = ? WHERE task_id = ?;", &ids);
+
+    // A context the other task already has is left behind, and removed below
+    execute(
+        db,
+        "UPDATE OR IGNORE contexts SET task_id = ? WHERE task_id = ?;",
+        &ids,
+    );
+    execute(db, "DELETE FROM contexts WHERE task_id = ?;", &ids[1..]);
+    execute(db, "DELETE FROM tasks WHERE id = ?;", &ids[1..]);
+}
+
+fn execute(db: &sqlite::Connection, query: &str, values: &[sqlite::Value]) {
+    let mut cursor = db.prepare(query).unwrap().into_cursor();
+    cursor.bind(values).unwrap();
+
+    if let Err(err) = cursor.next() {
+        panic!("Migration failed:\r\n{}\r\n\r\n{:?}\r\n", query, err);
+    }
 }
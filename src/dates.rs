@@ -0,0 +1,374 @@
+use crate::config;
+use crate::utils;
+
+use chrono::{
+    Datelike, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+    Weekday,
+};
+use chrono_tz::Tz;
+
+// The timezone in which timestamps are turned into days. By default every
+// entry uses the offset it was logged with, which is the local time of the
+// user at that moment, but this can be overridden by one zone for all.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Zone {
+    #[default]
+    Logged,
+    Local,
+    Fixed(FixedOffset),
+    Named(Tz),
+}
+
+impl Zone {
+    // Accepts `local`, `logged`, offsets like `-07:00` and names like `Europe/Amsterdam`
+    pub fn parse(input: &str) -> Result<Zone, String> {
+        let input = input.trim();
+
+        match input.to_lowercase().as_str() {
+            "local" => return Ok(Zone::Local),
+            "logged" => return Ok(Zone::Logged),
+            "utc" | "z" => return Ok(Zone::Fixed(FixedOffset::east(0))),
+            _ => {}
+        }
+
+        if let Some(offset) = parse_offset(input) {
+            return Ok(Zone::Fixed(offset));
+        }
+
+        input.parse::<Tz>().map(Zone::Named).map_err(|_| {
+            format!(
+                "`{}` is not a known timezone, try something like `America/Los_Angeles` or `-07:00`",
+                input
+            )
+        })
+    }
+
+    // The zone configured with `git config timecop.timezone`, if any
+    pub fn from_config() -> Result<Zone, String> {
+        match config::get_string("timecop.timezone") {
+            Some(zone) => Zone::parse(&zone),
+            None => Ok(Zone::Logged),
+        }
+    }
+
+    // What day is it today in this zone?
+    pub fn today(&self) -> NaiveDate {
+        let now = Utc::now().naive_utc();
+        self.localize(&now, utils::local_utc_offset(&now)).date()
+    }
+
+    // Convert a local time in this zone to UTC, there is no such thing for
+    // `Logged` as every entry has its own offset.
+    pub fn utc_of(&self, datetime: &NaiveDateTime) -> Option<NaiveDateTime> {
+        match self {
+            Zone::Logged => None,
+            Zone::Local => utils::local_to_utc(datetime),
+            Zone::Fixed(offset) => offset
+                .from_local_datetime(datetime)
+                .earliest()
+                .map(|d| d.naive_utc()),
+            Zone::Named(tz) => tz
+                .from_local_datetime(datetime)
+                .earliest()
+                .map(|d| d.naive_utc()),
+        }
+    }
+
+    // When the day starts in UTC. Where clocks skip midnight for daylight
+    // saving time the day starts at the first minute that does exist.
+    pub fn start_of_day(&self, date: NaiveDate) -> Option<NaiveDateTime> {
+        (0..24 * 60)
+            .map(|minutes| date.and_hms(0, 0, 0) + Duration::minutes(minutes))
+            .find_map(|datetime| self.utc_of(&datetime))
+    }
+
+    // Convert a UTC timestamp, logged with the given offset (in minutes), to this zone
+    pub fn localize(&self, datetime: &NaiveDateTime, utc_offset: i32) -> NaiveDateTime {
+        match self {
+            Zone::Logged => *datetime + Duration::minutes(utc_offset as i64),
+            Zone::Local => Local.from_utc_datetime(datetime).naive_local(),
+            Zone::Fixed(offset) => offset.from_utc_datetime(datetime).naive_local(),
+            Zone::Named(tz) => tz.from_utc_datetime(datetime).naive_local(),
+        }
+    }
+}
+
+// A range of days, both ends are inclusive and either end can be left open
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DateRange {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl DateRange {
+    pub fn day(date: NaiveDate) -> DateRange {
+        DateRange {
+            from: Some(date),
+            to: Some(date),
+        }
+    }
+
+    pub fn week_of(date: NaiveDate, week_start: Weekday) -> DateRange {
+        let from = date - Duration::days(days_since(date, week_start, false));
+        DateRange {
+            from: Some(from),
+            to: Some(from + Duration::days(6)),
+        }
+    }
+
+    pub fn month_of(date: NaiveDate) -> DateRange {
+        let from = date.with_day(1).unwrap();
+        let next = if from.month() == 12 {
+            NaiveDate::from_ymd(from.year() + 1, 1, 1)
+        } else {
+            NaiveDate::from_ymd(from.year(), from.month() + 1, 1)
+        };
+
+        DateRange {
+            from: Some(from),
+            to: Some(next - Duration::days(1)),
+        }
+    }
+
+    pub fn is_bounded(&self) -> bool {
+        self.from.is_some() || self.to.is_some()
+    }
+
+    // How the range is shown at the top of a summary
+    pub fn describe(&self) -> String {
+        let format = |date: NaiveDate| date.format("%-e %B, %Y").to_string();
+
+        match (self.from, self.to) {
+            (Some(from), Some(to)) if from == to => format(from),
+            (Some(from), Some(to)) => format!("{} - {}", format(from), format(to)),
+            (Some(from), None) => format!("since {}", format(from)),
+            (None, Some(to)) => format!("up to {}", format(to)),
+            (None, None) => "".to_string(),
+        }
+    }
+}
+
+// The first day of the week, `git config timecop.weekStart sunday`
+pub fn week_start() -> Weekday {
+    config::get_string("timecop.weekStart")
+        .and_then(|day| day.trim().parse::<Weekday>().ok())
+        .unwrap_or(Weekday::Mon)
+}
+
+// Parse a date as typed by the user, relative to `today`. This understands
+// `today`, `yesterday`, `3 days ago`, weekdays (`friday` is the most recent
+// friday, `last friday` the one before today) and `2021-06-25`.
+pub fn parse_date(input: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let input = input.trim().to_lowercase();
+    let words: Vec<&str> = input.split_whitespace().collect();
+
+    match words.as_slice() {
+        ["today"] | ["now"] => Ok(today),
+        ["yesterday"] => Ok(today - Duration::days(1)),
+        [days, "days", "ago"] | [days, "day", "ago"] => days
+            .parse::<u32>()
+            .ok()
+            .and_then(|days| today.checked_sub_signed(Duration::days(days as i64)))
+            .ok_or_else(|| invalid_date(&input)),
+        ["last", weekday] => match weekday.parse::<Weekday>() {
+            Ok(weekday) => Ok(today - Duration::days(days_since(today, weekday, true))),
+            Err(_) => Err(invalid_date(&input)),
+        },
+        [word] => match word.parse::<Weekday>() {
+            Ok(weekday) => Ok(today - Duration::days(days_since(today, weekday, false))),
+            Err(_) => NaiveDate::parse_from_str(word, "%Y-%m-%d").map_err(|_| invalid_date(&input)),
+        },
+        _ => Err(invalid_date(&input)),
+    }
+}
+
+// Parse a time of day, like `14:00`
+pub fn parse_time(input: &str) -> Result<NaiveTime, String> {
+    let input = input.trim();
+
+    NaiveTime::parse_from_str(input, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(input, "%H:%M:%S"))
+        .map_err(|_| format!("`{}` is not a valid time, try something like 14:00", input))
+}
+
+// Parse a date with an optional time (`last friday 14:00`), or just a time
+// (`14:00`, which means today). Without a time the time of `now` is used.
+pub fn parse_datetime(input: &str, now: NaiveDateTime) -> Result<NaiveDateTime, String> {
+    let input = input.trim();
+
+    if let Ok(date) = parse_date(input, now.date()) {
+        return Ok(date.and_time(now.time()));
+    }
+
+    if let Ok(time) = parse_time(input) {
+        return Ok(now.date().and_time(time));
+    }
+
+    // Split the time off the end, dates can be more than one word
+    let split = input.rfind(|c: char| c.is_whitespace() || c == 'T');
+    match split {
+        Some(index) => {
+            let date = parse_date(&input[..index], now.date())?;
+            let time = parse_time(&input[index + 1..])?;
+            Ok(date.and_time(time))
+        }
+        None => Err(invalid_date(input)),
+    }
+}
+
+// Work out when a log entry should be stamped, based on the `--date` and
+// `--at` arguments (both in local time, `--at` overrides the time of day).
+// The result is in UTC, ready to be stored, and can not be in the future.
+pub fn parse_backdate(date: Option<&str>, at: Option<&str>) -> Result<NaiveDateTime, String> {
+    let now = Local::now().naive_local();
+
+    let mut datetime = match date {
+        Some(date) => parse_datetime(date, now)?,
+        None => now,
+    };
+
+    if let Some(at) = at {
+        datetime = datetime.date().and_time(parse_time(at)?);
+    }
+
+    if datetime > now {
+        return Err(format!(
+            "{} is in the future, you can only log time you already spent",
+            datetime.format("%A %-e %B, %Y %H:%M")
+        ));
+    }
+
+    utils::local_to_utc(&datetime)
+        .ok_or_else(|| format!("{} does not exist in your timezone", datetime))
+}
+
+fn days_since(today: NaiveDate, weekday: Weekday, last: bool) -> i64 {
+    let days = (7 + today.weekday().num_days_from_monday() as i64
+        - weekday.num_days_from_monday() as i64)
+        % 7;
+
+    if last && days == 0 {
+        7
+    } else {
+        days
+    }
+}
+
+fn invalid_date(input: &str) -> String {
+    format!(
+        "`{}` is not a valid date, try something like `yesterday`, `last friday 14:00` or `2021-06-25`",
+        input
+    )
+}
+
+// `+02:00`, `-0700` or `+2`
+fn parse_offset(input: &str) -> Option<FixedOffset> {
+    let sign = match input.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+
+    let digits: String = input[1..].chars().filter(|c| *c != ':').collect();
+    let (hours, minutes) = match digits.len() {
+        1 | 2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (
+            digits[..2].parse::<i32>().ok()?,
+            digits[2..].parse::<i32>().ok()?,
+        ),
+        _ => return None,
+    };
+
+    if hours > 14 || minutes >= 60 {
+        return None;
+    }
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A Wednesday
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd(2021, 6, 23)
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd(2021, 6, day)
+    }
+
+    #[test]
+    fn parses_relative_days() {
+        assert_eq!(parse_date("today", today()), Ok(today()));
+        assert_eq!(parse_date("now", today()), Ok(today()));
+        assert_eq!(parse_date("yesterday", today()), Ok(date(22)));
+        assert_eq!(parse_date("3 days ago", today()), Ok(date(20)));
+        assert_eq!(parse_date("1 day ago", today()), Ok(date(22)));
+        assert_eq!(parse_date("0 days ago", today()), Ok(today()));
+    }
+
+    #[test]
+    fn parses_weekdays() {
+        assert_eq!(parse_date("friday", today()), Ok(date(18)));
+        assert_eq!(parse_date("wednesday", today()), Ok(today()));
+        assert_eq!(parse_date("last friday", today()), Ok(date(18)));
+        assert_eq!(parse_date("last wednesday", today()), Ok(date(16)));
+        assert_eq!(parse_date("Tue", today()), Ok(date(22)));
+    }
+
+    #[test]
+    fn parses_iso_dates() {
+        assert_eq!(parse_date("2021-06-25", today()), Ok(date(25)));
+        assert!(parse_date("2021-02-30", today()).is_err());
+    }
+
+    #[test]
+    fn rejects_days_ago_out_of_bounds() {
+        assert!(parse_date("-3 days ago", today()).is_err());
+        assert!(parse_date("99999999999 days ago", today()).is_err());
+        assert!(parse_date("4000000000 days ago", today()).is_err());
+    }
+
+    #[test]
+    fn parses_dates_with_a_time() {
+        let now = today().and_hms(9, 30, 0);
+
+        assert_eq!(parse_datetime("14:00", now), Ok(today().and_hms(14, 0, 0)));
+        assert_eq!(
+            parse_datetime("last friday 14:00", now),
+            Ok(date(18).and_hms(14, 0, 0))
+        );
+        assert_eq!(
+            parse_datetime("2021-06-20T08:15", now),
+            Ok(date(20).and_hms(8, 15, 0))
+        );
+        assert_eq!(
+            parse_datetime("yesterday", now),
+            Ok(date(22).and_hms(9, 30, 0))
+        );
+    }
+
+    #[test]
+    fn starts_the_day_after_a_gap_at_midnight() {
+        // Chile moved its clocks from midnight to 01:00 on this day
+        let zone = Zone::parse("America/Santiago").unwrap();
+        let date = NaiveDate::from_ymd(2021, 9, 5);
+
+        assert_eq!(zone.utc_of(&date.and_hms(0, 0, 0)), None);
+        assert_eq!(zone.start_of_day(date), Some(date.and_hms(4, 0, 0)));
+        assert_eq!(
+            zone.start_of_day(date.succ()),
+            Some(date.succ().and_hms(3, 0, 0))
+        );
+        assert_eq!(Zone::Logged.start_of_day(date), None);
+    }
+
+    #[test]
+    fn rejects_unknown_input() {
+        assert!(parse_date("someday", today()).is_err());
+        assert!(parse_date("last someday", today()).is_err());
+        assert!(parse_date("", today()).is_err());
+    }
+}
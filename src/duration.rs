@@ -0,0 +1,220 @@
+// Anything longer is a typo, and would not fit in the database either
+const MAX_MINUTES: usize = 100_000 * 60;
+
+// Parse a duration as typed by the user into minutes. A bare number is in
+// minutes (`90`), but hours and minutes can be written out (`1h30`, `1h 30m`,
+// `1.5h`) or be written like a clock (`01:30`). Zero is not a duration.
+pub fn parse_minutes(input: &str) -> Result<usize, String> {
+    let input: String = input
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+
+    if input.is_empty() {
+        return Err("Please enter the time spent, like 90, 1h30 or 1.5h".to_string());
+    }
+
+    if input.starts_with('-') {
+        return Err(format!("The time spent can't be negative (`{}`)", input));
+    }
+
+    let minutes = if input.contains(':') {
+        parse_clock(&input)?
+    } else if input.chars().all(|c| c.is_ascii_digit()) {
+        input.parse::<usize>().map_err(|_| invalid(&input))?
+    } else if input.parse::<f64>().is_ok() {
+        return Err(format!(
+            "`{}` is ambiguous, did you mean {}m or {}h?",
+            input, input, input
+        ));
+    } else {
+        parse_units(&input)?
+    };
+
+    if minutes == 0 {
+        return Err("The time spent has to be more than zero minutes".to_string());
+    }
+
+    if minutes > MAX_MINUTES {
+        return Err(too_long(&input));
+    }
+
+    Ok(minutes)
+}
+
+// Parse an estimate, where a bare number is in hours (`8`, `1.5`) instead of
+// minutes. Anything else is parsed like the time spent (`6h30`, `90m`).
+pub fn parse_hours(input: &str) -> Result<usize, String> {
+    let trimmed = input.trim();
+
+    match trimmed.parse::<f64>() {
+        Ok(hours) if hours.is_finite() && hours * 60.0 > MAX_MINUTES as f64 => {
+            Err(too_long(trimmed))
+        }
+        Ok(hours) if hours.is_finite() && hours > 0.0 => Ok((hours * 60.0).round() as usize),
+        Ok(_) => Err("The estimate has to be more than zero hours".to_string()),
+        Err(_) => parse_minutes(trimmed),
+    }
+}
+
+// `1:30` or `01:30`
+fn parse_clock(input: &str) -> Result<usize, String> {
+    let parts: Vec<&str> = input.split(':').collect();
+    if parts.len() != 2 {
+        return Err(invalid(input));
+    }
+
+    let hours = parts[0].parse::<usize>().map_err(|_| invalid(input))?;
+    let minutes = parts[1].parse::<usize>().map_err(|_| invalid(input))?;
+    if minutes >= 60 {
+        return Err(invalid(input));
+    }
+
+    hours
+        .checked_mul(60)
+        .and_then(|hours| hours.checked_add(minutes))
+        .ok_or_else(|| invalid(input))
+}
+
+#[derive(PartialEq)]
+enum Unit {
+    Hours,
+    Minutes,
+}
+
+// `1h30`, `1h30m`, `1.5h`, `90m`, `2hours`. Hours come first, and only the
+// minutes right after the hours can go without a unit.
+fn parse_units(input: &str) -> Result<usize, String> {
+    let mut total = 0.0;
+    let mut number = String::new();
+    let mut unit = String::new();
+    let mut last: Option<Unit> = None;
+
+    // Walk through the input as pairs of numbers and units
+    let mut chars = input.chars().peekable();
+    while chars.peek().is_some() {
+        number.clear();
+        unit.clear();
+
+        while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+            number.push(*c);
+            chars.next();
+        }
+
+        while let Some(c) = chars.peek().filter(|c| c.is_ascii_alphabetic()) {
+            unit.push(*c);
+            chars.next();
+        }
+
+        let value = number.parse::<f64>().map_err(|_| invalid(input))?;
+        let multiplier = match unit.as_str() {
+            "h" | "hr" | "hrs" | "hour" | "hours" if last.is_none() => {
+                last = Some(Unit::Hours);
+                60.0
+            }
+            "m" | "min" | "mins" | "minute" | "minutes" if last != Some(Unit::Minutes) => {
+                last = Some(Unit::Minutes);
+                1.0
+            }
+            // The minutes in `1h30` don't need a unit
+            "" if last == Some(Unit::Hours) && chars.peek().is_none() => {
+                last = Some(Unit::Minutes);
+                1.0
+            }
+            _ => return Err(invalid(input)),
+        };
+
+        total += value * multiplier;
+    }
+
+    if !total.is_finite() || total > MAX_MINUTES as f64 {
+        return Err(too_long(input));
+    }
+
+    Ok(total.round() as usize)
+}
+
+fn too_long(input: &str) -> String {
+    format!(
+        "`{}` is too long, a duration can be at most {} hours",
+        input,
+        MAX_MINUTES / 60
+    )
+}
+
+fn invalid(input: &str) -> String {
+    format!(
+        "`{}` is not a valid duration, try something like 90, 1h30 or 1.5h",
+        input
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_format() {
+        assert_eq!(parse_minutes("90"), Ok(90));
+        assert_eq!(parse_minutes("1h30"), Ok(90));
+        assert_eq!(parse_minutes("1h 30m"), Ok(90));
+        assert_eq!(parse_minutes("1.5h"), Ok(90));
+        assert_eq!(parse_minutes("01:30"), Ok(90));
+        assert_eq!(parse_minutes("1:30"), Ok(90));
+        assert_eq!(parse_minutes("90m"), Ok(90));
+        assert_eq!(parse_minutes("2 hours"), Ok(120));
+        assert_eq!(parse_minutes(" 1H30M "), Ok(90));
+    }
+
+    #[test]
+    fn rejects_ambiguous_and_invalid_input() {
+        assert!(parse_minutes("1.5").is_err());
+        assert!(parse_minutes("").is_err());
+        assert!(parse_minutes("0").is_err());
+        assert!(parse_minutes("-30").is_err());
+        assert!(parse_minutes("1:60").is_err());
+        assert!(parse_minutes("1:2:3").is_err());
+        assert!(parse_minutes("1h2h").is_err());
+        assert!(parse_minutes("30x").is_err());
+    }
+
+    #[test]
+    fn rejects_clocks_that_overflow() {
+        let input = format!("{}:00", usize::MAX / 2);
+        assert!(parse_minutes(&input).is_err());
+        assert!(parse_minutes("99999999999999999999999:00").is_err());
+    }
+
+    #[test]
+    fn rejects_durations_that_are_too_long() {
+        assert!(parse_minutes("99999999999999999999h").is_err());
+        assert!(parse_minutes("9223372036854775808").is_err());
+        assert!(parse_minutes("6000001").is_err());
+        assert!(parse_minutes("100001h").is_err());
+        assert_eq!(parse_minutes("6000000"), Ok(6_000_000));
+        assert_eq!(parse_minutes("100000h"), Ok(6_000_000));
+    }
+
+    #[test]
+    fn only_allows_a_bare_number_right_after_hours() {
+        assert!(parse_minutes("1h30m15").is_err());
+        assert!(parse_minutes("30m1h").is_err());
+        assert!(parse_minutes("30m15m").is_err());
+        assert!(parse_minutes("30m15").is_err());
+        assert_eq!(parse_minutes("1h30"), Ok(90));
+    }
+
+    #[test]
+    fn parses_hours_for_estimates() {
+        assert_eq!(parse_hours("8"), Ok(480));
+        assert_eq!(parse_hours("1.5"), Ok(90));
+        assert_eq!(parse_hours("6h30"), Ok(390));
+        assert!(parse_hours("0").is_err());
+        assert!(parse_hours("1e30").is_err());
+        assert!(parse_hours("1e300").is_err());
+        assert!(parse_hours("inf").is_err());
+        assert!(parse_hours("100001").is_err());
+    }
+}
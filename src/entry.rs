@@ -0,0 +1,176 @@
+use crate::database;
+use crate::project::Project;
+use crate::utils;
+
+use chrono::NaiveDateTime;
+use std::vec::Vec;
+
+// A single log entry (a row in `task_logs`) along with the name of the task
+// it belongs to, so it can be shown without looking the task up separately.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub id: usize,
+    pub task_id: usize,
+    pub task_name: String,
+    pub name: String,
+    pub minutes: usize,
+    pub utc_offset: i32,
+    pub inserted_at: NaiveDateTime,
+    // Overrides whether the time spent on the task is billable
+    pub billable: Option<bool>,
+}
+
+impl Entry {
+    // Only entries of the given project, ids are shared by all projects
+    pub fn get_for_project(project: &Project, id: usize) -> Option<Entry> {
+        get_project_entry(project, id)
+    }
+
+    pub fn list_for_project(project: &Project) -> Vec<Entry> {
+        list_project_entries(project)
+    }
+
+    // Move the entry to another (UTC) date, in the current local timezone
+    pub fn set_date(&mut self, inserted_at: NaiveDateTime) {
+        self.utc_offset = utils::local_utc_offset(&inserted_at);
+        self.inserted_at = inserted_at;
+    }
+
+    pub fn update(&self) {
+        update_entry(self)
+    }
+
+    pub fn delete(&self) {
+        delete_entry(self)
+    }
+}
+
+fn get_project_entry(project: &Project, id: usize) -> Option<Entry> {
+    let mut result: Option<Entry> = None;
+
+    database::with_db(|db| {
+        let mut cursor = db
+            .prepare(
+                "
+                SELECT l.id, l.task_id, t.name, l.name, l.minutes, l.utc_offset, l.inserted_at, l.billable
+                FROM task_logs l
+                LEFT JOIN tasks t ON t.id = l.task_id
+                WHERE l.id = ? AND t.project_id = ?;",
+            )
+            .unwrap()
+            .into_cursor();
+
+        cursor
+            .bind(&[
+                sqlite::Value::Integer(id as i64),
+                sqlite::Value::Integer(project.id as i64),
+            ])
+            .unwrap();
+
+        result = row_to_entry(cursor.next());
+    });
+
+    result
+}
+
+fn list_project_entries(project: &Project) -> Vec<Entry> {
+    let mut results: Vec<Entry> = Vec::new();
+
+    database::with_db(|db| {
+        let mut cursor = db
+            .prepare(
+                "
+                SELECT l.id, l.task_id, t.name, l.name, l.minutes, l.utc_offset, l.inserted_at, l.billable
+                FROM task_logs l
+                LEFT JOIN tasks t ON t.id = l.task_id
+                WHERE t.project_id = ?
+                ORDER BY l.inserted_at DESC, l.id DESC;",
+            )
+            .unwrap()
+            .into_cursor();
+
+        cursor
+            .bind(&[sqlite::Value::Integer(project.id as i64)])
+            .unwrap();
+
+        results = rows_to_entries(cursor);
+    });
+
+    results
+}
+
+// This function will write the (changed) fields of the entry back to the
+// database, bumping its "last updated" timestamp along the way.
+fn update_entry(entry: &Entry) {
+    database::with_db(|db| {
+        let mut cursor = db
+            .prepare(
+                "
+                UPDATE task_logs
+                SET task_id = ?, name = ?, minutes = ?, utc_offset = ?, inserted_at = ?,
+                    billable = ?, updated_at = DATETIME()
+                WHERE id = ?;",
+            )
+            .unwrap()
+            .into_cursor();
+
+        cursor
+            .bind(&[
+                sqlite::Value::Integer(entry.task_id as i64),
+                sqlite::Value::String(entry.name.to_string()),
+                sqlite::Value::Integer(entry.minutes as i64),
+                sqlite::Value::Integer(entry.utc_offset as i64),
+                sqlite::Value::String(utils::datetime_to_sql(&entry.inserted_at)),
+                entry
+                    .billable
+                    .map_or(sqlite::Value::Null, |b| sqlite::Value::Integer(b as i64)),
+                sqlite::Value::Integer(entry.id as i64),
+            ])
+            .unwrap();
+
+        cursor.next().unwrap();
+    });
+}
+
+fn delete_entry(entry: &Entry) {
+    database::with_db(|db| {
+        let mut cursor = db
+            .prepare("DELETE FROM task_logs WHERE id = ?;")
+            .unwrap()
+            .into_cursor();
+
+        cursor
+            .bind(&[sqlite::Value::Integer(entry.id as i64)])
+            .unwrap();
+
+        cursor.next().unwrap();
+    });
+}
+
+fn rows_to_entries(mut cursor: sqlite::Cursor) -> Vec<Entry> {
+    let mut results: Vec<Entry> = Vec::new();
+    while let Some(entry) = row_to_entry(cursor.next()) {
+        results.push(entry);
+    }
+
+    results
+}
+
+fn row_to_entry(row: Result<Option<&[sqlite::Value]>, sqlite::Error>) -> Option<Entry> {
+    let columns = match row {
+        Ok(None) => return None,
+        Ok(columns) => columns.unwrap(),
+        Err(_) => return None,
+    };
+
+    Some(Entry {
+        id: columns[0].as_integer().unwrap() as usize,
+        task_id: columns[1].as_integer().unwrap() as usize,
+        task_name: columns[2].as_string().unwrap_or("").to_string(),
+        name: columns[3].as_string().unwrap().to_string(),
+        minutes: columns[4].as_integer().unwrap() as usize,
+        utc_offset: columns[5].as_integer().unwrap() as i32,
+        inserted_at: utils::sql_to_datetime(columns[6].as_string()).unwrap(),
+        billable: columns[7].as_integer().map(|billable| billable != 0),
+    })
+}
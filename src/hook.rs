@@ -0,0 +1,380 @@
+use crate::utils;
+
+use std::fs;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+// The lines around our part of the hook, everything in between is ours to
+// replace or remove, everything outside of it is left alone.
+const BEGIN: &str = "# >>> timecop >>>";
+const END: &str = "# <<< timecop <<<";
+
+// What a hook timecop starts itself begins with
+const NEW_HOOK: &str = "#!/usr/bin/env bash\n";
+
+// Hooks from before the block took over the whole file
+const LEGACY_MARKER: &str = "# Start a new commit based log entry";
+
+// The lines those hooks consisted of, besides running timecop itself
+const LEGACY_LINES: [&str; 7] = [
+    "# Offer a nice interactive experience",
+    "exec < /dev/tty",
+    "exec < /dev/stdin",
+    "exec < /dev/stderr",
+    LEGACY_MARKER,
+    "# Close stdin again",
+    "exec <&-",
+];
+
+#[derive(Debug, PartialEq)]
+pub enum HookState {
+    // There is no post-commit hook at all
+    Missing,
+    // There is a post-commit hook, but timecop isn't part of it
+    Absent,
+    // The whole hook was written by an older version of timecop
+    Legacy,
+    // The block is there, but runs another timecop binary
+    Outdated,
+    Installed,
+}
+
+// The post-commit hook git runs for this repository. Tools like husky point
+// `core.hooksPath` somewhere else, which is relative to the worktree root.
+pub fn hook_path(repo: &git2::Repository) -> PathBuf {
+    let hooks_path = repo
+        .config()
+        .ok()
+        .and_then(|config| config.get_path("core.hooksPath").ok());
+
+    let dir = match hooks_path {
+        Some(dir) if dir.is_absolute() => dir,
+        Some(dir) => repo.workdir().unwrap_or_else(|| repo.path()).join(dir),
+        None => utils::get_common_dir(repo).join("hooks"),
+    };
+
+    dir.join("post-commit")
+}
+
+pub fn state(path: &Path) -> HookState {
+    match fs::read_to_string(path) {
+        Ok(contents) => state_of(&contents),
+        Err(_) => HookState::Missing,
+    }
+}
+
+fn state_of(contents: &str) -> HookState {
+    match find_block(contents) {
+        Some((start, end)) if contents[start..end] == replacement(&contents[start..end]) => {
+            HookState::Installed
+        }
+        Some(_) => HookState::Outdated,
+        None if is_legacy(contents) => HookState::Legacy,
+        None => HookState::Absent,
+    }
+}
+
+// Adds the block to the hook, or brings it up to date. Running it twice
+// leaves the hook just like running it once.
+pub fn install(path: &Path) -> io::Result<()> {
+    let contents = fs::read_to_string(path).unwrap_or_default();
+    let contents = with_block(&contents)?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    fs::write(path, contents)?;
+
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_mode(permissions.mode() | 0o755);
+    fs::set_permissions(path, permissions)
+}
+
+// Removes only the block (or the lines of an older timecop), the hook itself
+// only goes when nothing else is left. Returns whether there was anything to
+// remove.
+pub fn uninstall(path: &Path) -> io::Result<bool> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(_) => return Ok(false),
+    };
+
+    match without_block(&contents) {
+        Some(rest) if rest.is_empty() => fs::remove_file(path)?,
+        Some(rest) => fs::write(path, rest)?,
+        None => return Ok(false),
+    }
+
+    Ok(true)
+}
+
+// The hook with the block added, or brought up to date. A hook that isn't
+// run by a shell is left alone, the block is shell code.
+fn with_block(contents: &str) -> io::Result<String> {
+    let contents = if contents.trim().is_empty() {
+        NEW_HOOK.to_string()
+    } else if is_legacy(contents) && find_block(contents).is_none() {
+        match strip_legacy(contents) {
+            rest if is_empty_hook(&rest) => NEW_HOOK.to_string(),
+            rest => rest,
+        }
+    } else {
+        contents.to_string()
+    };
+
+    if let Some(interpreter) = foreign_interpreter(&contents) {
+        return Err(io::Error::other(format!(
+            "the hook runs {} instead of a shell, add `{}` to it yourself",
+            interpreter,
+            command()
+        )));
+    }
+
+    Ok(match find_block(&contents) {
+        Some((start, end)) => format!(
+            "{}{}{}",
+            &contents[..start],
+            replacement(&contents[start..end]),
+            &contents[end..]
+        ),
+        None => insert_block(&contents),
+    })
+}
+
+// The hook without the block, or without the lines of an older timecop. It
+// is empty when there is nothing left worth keeping, and there is nothing
+// when timecop wasn't part of the hook.
+fn without_block(contents: &str) -> Option<String> {
+    let rest = match find_block(contents) {
+        Some((start, end)) => format!("{}{}", &contents[..start], &contents[end..]),
+        None if is_legacy(contents) => strip_legacy(contents),
+        None => return None,
+    };
+
+    // A hook timecop started itself (or wrote all of) goes as a whole
+    if rest == NEW_HOOK || (find_block(contents).is_none() && is_empty_hook(&rest)) {
+        Some(String::new())
+    } else {
+        Some(rest)
+    }
+}
+
+// The block always starts and ends a line, so removing it gives back the
+// hook exactly as it was. A hook that didn't end with a newline got one in
+// front of the block, which goes along with it.
+fn find_block(contents: &str) -> Option<(usize, usize)> {
+    let start = contents.find(BEGIN)?;
+    let end = start + contents[start..].find(END)? + END.len();
+
+    if contents[end..].starts_with('\n') {
+        Some((start, end + 1))
+    } else if end == contents.len() && contents[..start].ends_with('\n') {
+        Some((start - 1, end))
+    } else {
+        Some((start, end))
+    }
+}
+
+// The up to date block, in the same place the old one took up
+fn replacement(old: &str) -> String {
+    if old.starts_with('\n') {
+        format!("\n{}", block().trim_end_matches('\n'))
+    } else {
+        block()
+    }
+}
+
+// At the end of the hook, but before a final `exit` or `exec` as nothing
+// after those runs
+fn insert_block(contents: &str) -> String {
+    let trimmed = contents.trim_end();
+    let last_line = trimmed.rfind('\n').map_or(0, |index| index + 1);
+
+    if last_line > 0 && ends_the_hook(&trimmed[last_line..]) {
+        format!(
+            "{}{}{}",
+            &contents[..last_line],
+            block(),
+            &contents[last_line..]
+        )
+    } else if contents.ends_with('\n') {
+        format!("{}{}", contents, block())
+    } else {
+        format!("{}\n{}", contents, block().trim_end_matches('\n'))
+    }
+}
+
+fn ends_the_hook(line: &str) -> bool {
+    matches!(line.split_whitespace().next(), Some("exit") | Some("exec"))
+}
+
+// The program in the shebang of the hook, when it isn't a shell. Without a
+// shebang the hook is run by the shell.
+fn foreign_interpreter(contents: &str) -> Option<String> {
+    let shebang = contents.lines().next()?.strip_prefix("#!")?;
+    let mut words = shebang.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+
+    // `#!/usr/bin/env python3`, or `#!/usr/bin/env -S bash -e`
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-'))?;
+    }
+
+    match program {
+        "sh" | "bash" | "zsh" | "dash" | "ksh" | "ash" => None,
+        program => Some(program.to_string()),
+    }
+}
+
+fn is_legacy(contents: &str) -> bool {
+    contents.contains(LEGACY_MARKER) && contents.contains(" log --commit")
+}
+
+// Leaves out the lines an older timecop wrote, along with the blank lines
+// that separated them from the rest
+fn strip_legacy(contents: &str) -> String {
+    let mut rest = String::new();
+    let mut after_legacy = false;
+
+    for line in contents.lines() {
+        let trimmed = line.trim();
+
+        if LEGACY_LINES.contains(&trimmed) || trimmed.ends_with(" log --commit") {
+            after_legacy = true;
+            continue;
+        }
+
+        if after_legacy && trimmed.is_empty() {
+            continue;
+        }
+
+        after_legacy = false;
+        rest.push_str(line);
+        rest.push('\n');
+    }
+
+    rest
+}
+
+// Nothing but a shebang and blank lines
+fn is_empty_hook(contents: &str) -> bool {
+    contents
+        .lines()
+        .all(|line| line.trim().is_empty() || line.starts_with("#!"))
+}
+
+// Reads from the terminal to offer a nice interactive experience, and never
+// fails the rest of the hook when there is no terminal to read from.
+fn block() -> String {
+    format!(
+        "{}
+# Added by `timecop hook install`, remove it with `timecop hook uninstall`
+{}
+{}
+",
+        BEGIN,
+        command(),
+        END
+    )
+}
+
+fn command() -> String {
+    format!(
+        "\"{}\" log --commit < /dev/tty || true",
+        std::env::current_exe().unwrap().to_str().unwrap()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(original: &str) {
+        let installed = with_block(original).unwrap();
+        assert!(installed.contains(&block().trim_end_matches('\n').to_string()));
+        assert_eq!(without_block(&installed), Some(original.to_string()));
+    }
+
+    #[test]
+    fn installing_twice_is_installing_once() {
+        for original in &[
+            "",
+            "#!/bin/sh\necho hi\n",
+            "#!/bin/sh\necho hi",
+            "#!/bin/sh\nexit 0\n",
+        ] {
+            let once = with_block(original).unwrap();
+            assert_eq!(with_block(&once).unwrap(), once);
+        }
+    }
+
+    #[test]
+    fn uninstalling_gives_back_the_hook() {
+        round_trip("#!/bin/sh\necho hi\n");
+        round_trip("#!/bin/sh\n");
+        round_trip("echo hi\n");
+    }
+
+    #[test]
+    fn goes_before_a_final_exit() {
+        let installed = with_block("#!/bin/sh\necho hi\nexit 0\n").unwrap();
+        assert!(installed.ends_with(&format!("{}exit 0\n", block())));
+        round_trip("#!/bin/sh\necho hi\nexit 0\n");
+        round_trip("#!/bin/sh\necho hi\nexec other-hook \"$@\"\n");
+    }
+
+    #[test]
+    fn keeps_a_hook_without_a_trailing_newline() {
+        let installed = with_block("#!/bin/sh\necho hi").unwrap();
+        assert!(installed.starts_with("#!/bin/sh\necho hi\n# >>> timecop >>>"));
+        assert_eq!(state_of(&installed), HookState::Installed);
+        round_trip("#!/bin/sh\necho hi");
+        round_trip("#!/bin/sh\nexit 0");
+    }
+
+    #[test]
+    fn starts_and_removes_a_hook_of_its_own() {
+        let installed = with_block("").unwrap();
+        assert!(installed.starts_with(NEW_HOOK));
+        assert_eq!(without_block(&installed), Some(String::new()));
+        assert_eq!(without_block("#!/bin/sh\necho hi\n"), None);
+    }
+
+    #[test]
+    fn removes_only_the_legacy_lines() {
+        let legacy = "#!/usr/bin/env bash
+
+# Offer a nice interactive experience
+exec < /dev/tty
+exec < /dev/stdin
+exec < /dev/stderr
+
+# Start a new commit based log entry
+/usr/local/bin/timecop log --commit
+
+# Close stdin again
+exec <&-
+";
+        assert_eq!(without_block(legacy), Some(String::new()));
+
+        let extended = format!("{}echo after\n", legacy);
+        assert_eq!(
+            without_block(&extended),
+            Some("#!/usr/bin/env bash\n\necho after\n".to_string())
+        );
+
+        let installed = with_block(&extended).unwrap();
+        assert!(installed.starts_with("#!/usr/bin/env bash\n\necho after\n"));
+        assert!(!installed.contains(LEGACY_MARKER));
+    }
+
+    #[test]
+    fn refuses_hooks_that_are_not_shell_scripts() {
+        assert!(with_block("#!/usr/bin/env python3\nprint('hi')\n").is_err());
+        assert!(with_block("#!/usr/bin/node\n").is_err());
+        assert!(with_block("#!/usr/bin/env -S bash -e\necho hi\n").is_ok());
+        assert!(with_block("#!/bin/zsh\necho hi\n").is_ok());
+    }
+}
@@ -2,7 +2,11 @@ use crate::database;
 use crate::utils;
 
 pub fn set_ignore_flag() {
-    let remote_branch = get_remote_branch();
+    // Only a branch can be ignored, not a detached HEAD
+    let remote_branch = match get_remote_branch() {
+        Some(remote_branch) => remote_branch,
+        None => return,
+    };
 
     database::with_db(|db| {
         // List all tasks
@@ -32,7 +36,10 @@ pub fn set_ignore_flag() {
 
 pub fn get_ignore_flag() -> bool {
     let mut result = false;
-    let remote_branch = get_remote_branch();
+    let remote_branch = match get_remote_branch() {
+        Some(remote_branch) => remote_branch,
+        None => return result,
+    };
 
     database::with_db(|db| {
         // List all tasks
@@ -59,10 +66,8 @@ pub fn get_ignore_flag() -> bool {
     result
 }
 
-fn get_remote_branch() -> String {
-    // If the code comes here these should have been unwrapped before so I am
-    // assuming they're safe at this point.
-    let repo = utils::get_current_repo().unwrap();
-    let (remote, branch) = utils::get_repo_remote_and_branch(repo).unwrap();
-    format!("{}#{}", remote, branch)
+fn get_remote_branch() -> Option<String> {
+    let repo = utils::get_context_repo()?;
+    let (remote, branch) = utils::get_repo_remote_and_branch(repo)?;
+    Some(format!("{}#{}", remote, branch?))
 }
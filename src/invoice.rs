@@ -0,0 +1,333 @@
+use crate::database;
+use crate::dates::DateRange;
+use crate::project::Project;
+use crate::summary::Summary;
+
+use chrono::{Local, NaiveDate};
+use std::io;
+use std::vec::Vec;
+
+// An invoice for the time spent on a project in a range of days, with a line
+// for every task. All amounts are in cents, rates are per hour.
+#[derive(Debug)]
+pub struct Invoice {
+    // Only issued invoices have a number, a draft does not use one up
+    pub number: Option<usize>,
+    pub project: String,
+    pub currency: Option<String>,
+    pub range: DateRange,
+    pub date: NaiveDate,
+    pub lines: Vec<InvoiceLine>,
+    pub subtotal: i64,
+    pub tax_percentage: f64,
+    pub tax: i64,
+    pub total: i64,
+}
+
+// Non-billable time is listed, but never charged for
+#[derive(Debug)]
+pub struct InvoiceLine {
+    pub task: String,
+    pub minutes: usize,
+    pub billable: bool,
+    pub rate: i64,
+    pub amount: i64,
+}
+
+impl Invoice {
+    pub fn draft(
+        project: &Project,
+        summary: &Summary,
+        range: DateRange,
+        tax_percentage: f64,
+    ) -> Result<Invoice, String> {
+        draft_invoice(project, summary, range, tax_percentage)
+    }
+
+    // The number is only taken once `write` succeeded, an invoice that
+    // could not be written doesn't leave a gap in the numbers.
+    pub fn issue<F>(&mut self, project: &Project, write: F) -> Result<usize, String>
+    where
+        F: FnOnce(&Invoice) -> io::Result<()>,
+    {
+        issue_invoice(self, project, write)
+    }
+}
+
+// Price the time spent on every task at the rate of the task, or the rate of
+// the project when the task does not have one of its own. Non-billable time
+// does not need a rate.
+fn draft_invoice(
+    project: &Project,
+    summary: &Summary,
+    range: DateRange,
+    tax_percentage: f64,
+) -> Result<Invoice, String> {
+    let rates: Vec<(usize, Option<i64>)> = project
+        .list_tasks()
+        .iter()
+        .map(|t| (t.id, t.rate.or(project.rate)))
+        .collect();
+
+    let mut tasks = summary.tasks();
+    tasks.sort_by_key(|t| (t.id, !t.billable));
+
+    let mut lines: Vec<InvoiceLine> = Vec::new();
+    for task in tasks.iter().filter(|t| t.minutes > 0) {
+        if !task.billable {
+            lines.push(InvoiceLine {
+                task: task.name.to_string(),
+                minutes: task.minutes,
+                billable: false,
+                rate: 0,
+                amount: 0,
+            });
+            continue;
+        }
+
+        let rate = rates
+            .iter()
+            .find(|(id, _)| *id == task.id)
+            .and_then(|(_, rate)| *rate)
+            .ok_or_else(|| {
+                format!(
+                    "There is no hourly rate for `{}`, set one for the project with\r\n`timecop rate <amount>` or for the task with `timecop rate <amount> --task <name>`.",
+                    task.name
+                )
+            })?;
+
+        lines.push(InvoiceLine {
+            task: task.name.to_string(),
+            minutes: task.minutes,
+            billable: true,
+            rate,
+            amount: line_amount(task.minutes, rate),
+        });
+    }
+
+    let subtotal: i64 = lines.iter().map(|l| l.amount).sum();
+    let tax = (subtotal as f64 * tax_percentage / 100.0).round() as i64;
+
+    Ok(Invoice {
+        number: None,
+        project: project.name.to_string(),
+        currency: project.currency.clone(),
+        range,
+        date: Local::today().naive_local(),
+        lines,
+        subtotal,
+        tax_percentage,
+        tax,
+        total: subtotal + tax,
+    })
+}
+
+// The rate is per hour, the amount is rounded to the nearest cent
+fn line_amount(minutes: usize, rate: i64) -> i64 {
+    (minutes as i64 * rate + 30) / 60
+}
+
+// Store the invoice and its lines under the next number, numbers are never
+// handed out twice so an invoice that was sent can always be traced back.
+fn issue_invoice<F>(invoice: &mut Invoice, project: &Project, write: F) -> Result<usize, String>
+where
+    F: FnOnce(&Invoice) -> io::Result<()>,
+{
+    let date_value = |date: Option<NaiveDate>| match date {
+        Some(date) => sqlite::Value::String(date.format("%Y-%m-%d").to_string()),
+        None => sqlite::Value::Null,
+    };
+
+    database::with_db(|db| {
+        db.execute("BEGIN;").unwrap();
+
+        let mut cursor = db
+            .prepare(
+                "
+                INSERT INTO invoices (
+                  number,
+                  project_id,
+                  period_from,
+                  period_to,
+                  currency,
+                  subtotal,
+                  tax_percentage,
+                  total,
+                  inserted_at,
+                  updated_at
+                )
+                SELECT COALESCE(MAX(number), 0) + 1, ?, ?, ?, ?, ?, ?, ?, DATETIME(), DATETIME()
+                FROM invoices;",
+            )
+            .unwrap()
+            .into_cursor();
+
+        cursor
+            .bind(&[
+                sqlite::Value::Integer(project.id as i64),
+                date_value(invoice.range.from),
+                date_value(invoice.range.to),
+                invoice
+                    .currency
+                    .clone()
+                    .map_or(sqlite::Value::Null, sqlite::Value::String),
+                sqlite::Value::Integer(invoice.subtotal),
+                sqlite::Value::Float(invoice.tax_percentage),
+                sqlite::Value::Integer(invoice.total),
+            ])
+            .unwrap();
+
+        if cursor.next().is_ok() {
+            cursor = db
+                .prepare("SELECT number FROM invoices WHERE id IN(SELECT last_insert_rowid());")
+                .unwrap()
+                .into_cursor();
+
+            if let Ok(Some(row)) = cursor.next() {
+                invoice.number = Some(row[0].as_integer().unwrap() as usize);
+            }
+        }
+
+        let number = match invoice.number {
+            Some(number) => number,
+            None => return,
+        };
+
+        for line in invoice.lines.iter() {
+            let mut cursor = db
+                .prepare(
+                    "
+                    INSERT INTO invoice_lines (
+                      invoice_id,
+                      task,
+                      minutes,
+                      billable,
+                      rate,
+                      amount,
+                      inserted_at,
+                      updated_at
+                    )
+                    SELECT id, ?, ?, ?, ?, ?, DATETIME(), DATETIME()
+                    FROM invoices
+                    WHERE number = ?;",
+                )
+                .unwrap()
+                .into_cursor();
+
+            cursor
+                .bind(&[
+                    sqlite::Value::String(line.task.to_string()),
+                    sqlite::Value::Integer(line.minutes as i64),
+                    sqlite::Value::Integer(line.billable as i64),
+                    sqlite::Value::Integer(line.rate),
+                    sqlite::Value::Integer(line.amount),
+                    sqlite::Value::Integer(number as i64),
+                ])
+                .unwrap();
+
+            if cursor.next().is_err() {
+                invoice.number = None;
+                return;
+            }
+        }
+    });
+
+    let result = match invoice.number {
+        Some(number) => write(invoice)
+            .map(|_| number)
+            .map_err(|err| format!("Unable to write the invoice: {}", err)),
+        None => Err("Unable to store the invoice, please try again.".to_string()),
+    };
+
+    database::with_db(|db| {
+        let query = if result.is_ok() {
+            "COMMIT;"
+        } else {
+            "ROLLBACK;"
+        };
+        db.execute(query).unwrap();
+    });
+
+    if result.is_err() {
+        invoice.number = None;
+    }
+
+    result
+}
+
+// Parse an amount of money as typed by the user (`85`, `85.50` or `85,50`)
+// into cents.
+pub fn parse_amount(input: &str) -> Result<i64, String> {
+    let input = input.trim().replace(',', ".");
+    let invalid = || format!("`{}` is not an amount, write it like 85 or 85.50", input);
+
+    let (whole, fraction) = match input.find('.') {
+        Some(index) => (&input[..index], &input[index + 1..]),
+        None => (input.as_str(), ""),
+    };
+
+    if whole.is_empty()
+        || fraction.len() > 2
+        || !whole.chars().all(|c| c.is_ascii_digit())
+        || !fraction.chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let whole = whole.parse::<i64>().map_err(|_| invalid())?;
+    let cents = format!("{:0<2}", fraction).parse::<i64>().unwrap_or(0);
+
+    Ok(whole * 100 + cents)
+}
+
+// Cents as an amount with two decimals, like `1234.50`
+pub fn format_amount(cents: i64) -> String {
+    format!("{}.{:02}", cents / 100, cents % 100)
+}
+
+// Decimal hours with two decimals, like `1.50`
+pub fn format_hours(minutes: usize) -> String {
+    format!("{:.2}", minutes as f64 / 60.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_amounts_into_cents() {
+        assert_eq!(parse_amount("85"), Ok(8500));
+        assert_eq!(parse_amount("85.5"), Ok(8550));
+        assert_eq!(parse_amount("85.50"), Ok(8550));
+        assert_eq!(parse_amount("85,05"), Ok(8505));
+        assert_eq!(parse_amount(" 0.99 "), Ok(99));
+    }
+
+    #[test]
+    fn rejects_invalid_amounts() {
+        assert!(parse_amount("").is_err());
+        assert!(parse_amount(".50").is_err());
+        assert!(parse_amount("85.505").is_err());
+        assert!(parse_amount("-85").is_err());
+        assert!(parse_amount("85 EUR").is_err());
+        assert!(parse_amount("1.2.3").is_err());
+    }
+
+    #[test]
+    fn rounds_line_amounts_to_the_nearest_cent() {
+        assert_eq!(line_amount(60, 8500), 8500);
+        assert_eq!(line_amount(90, 8500), 12750);
+        // 7 minutes at 1.00 per hour is 11.67 cents
+        assert_eq!(line_amount(7, 100), 12);
+        // 1 minute at 0.30 per hour is exactly half a cent, which rounds up
+        assert_eq!(line_amount(1, 30), 1);
+        assert_eq!(line_amount(1, 29), 0);
+    }
+
+    #[test]
+    fn formats_amounts_and_hours() {
+        assert_eq!(format_amount(123450), "1234.50");
+        assert_eq!(format_amount(5), "0.05");
+        assert_eq!(format_hours(90), "1.50");
+    }
+}
@@ -1,18 +1,29 @@
 mod commands;
+mod config;
 mod database;
+mod dates;
+mod duration;
+mod entry;
+mod hook;
 mod ignore;
+mod invoice;
 mod project;
+mod render;
 mod state;
 mod summary;
 mod task;
+mod timer;
 mod utils;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-use clap::{App, AppSettings, Arg, SubCommand};
+use clap::{App, AppSettings, Arg, ArgGroup, SubCommand};
 use state::State;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Every format known to the renderer registry
+    let formats = render::names();
+
     let matches = App::new("timecop")
         .about("helps you keep track of time spent working.")
         .version(VERSION)
@@ -48,32 +59,411 @@ each commit you make how much time you think you spent on it.")
                         .long("message")
                         .takes_value(true)
                         .required(false),
+                )
+                .arg(
+                    Arg::with_name("time")
+                        .help("the time spent (90, 1h30, 1.5h, 01:30)")
+                        .short("t")
+                        .long("time")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("task")
+                        .help("log the entry for this task (name or id)")
+                        .long("task")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("create-task")
+                        .help("log the entry for a new task with this name")
+                        .long("create-task")
+                        .takes_value(true)
+                        .conflicts_with("task")
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("yes")
+                        .help("accept the defaults instead of prompting (last commit message, suggested time)")
+                        .short("y")
+                        .long("yes")
+                        .takes_value(false)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("non-billable")
+                        .help("do not bill the time spent, whatever the task or project says")
+                        .long("non-billable")
+                        .takes_value(false)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("date")
+                        .help("log the entry on another day (yesterday, last friday 14:00, 2021-06-25)")
+                        .long("date")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("at")
+                        .help("log the entry at another time of day (14:00)")
+                        .long("at")
+                        .takes_value(true)
+                        .required(false),
                 ),
         )
-        .subcommand(
+        .subcommand(summary_args(
             SubCommand::with_name("output")
                 .about("output the tasks performed by day for this project")
                 .arg(
                     Arg::with_name("csv")
                         .long("csv")
-                        .help("export as CSV")
+                        .help("export as CSV, short for `--format csv`")
+                        .takes_value(false)
+                        .conflicts_with("format")
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .help("the format to output the summary in")
+                        .takes_value(true)
+                        .possible_values(&formats)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("template")
+                        .long("template")
+                        .help("render with your own template, by path or by name from ~/.config/timecop/templates")
+                        .takes_value(true)
+                        .conflicts_with_all(&["format", "csv"])
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("budget")
+                        .long("budget")
+                        .help("compare the time spent on every task to its estimate, as text or CSV")
+                        .takes_value(false)
+                        .conflicts_with("template")
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .short("o")
+                        .help("write to this file instead of the terminal")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("by")
+                        .long("by")
+                        .help("roll the output up by day, week or month")
+                        .takes_value(true)
+                        .possible_values(&["day", "week", "month"])
+                        .default_value("day"),
+                ),
+        ))
+        .subcommand(summary_args(
+            SubCommand::with_name("report")
+                .about("output the tasks performed by day for several projects")
+                .long_about(
+                    "This can run from any directory, it reports on the project of the current repository,
+the project given by `--project` or on every project with `--all-projects`.",
+                )
+                .arg(
+                    Arg::with_name("all-projects")
+                        .long("all-projects")
+                        .help("include every project")
+                        .takes_value(false)
+                        .conflicts_with("project"),
+                )
+                .arg(
+                    Arg::with_name("project")
+                        .long("project")
+                        .help("the name (or id) of the project to report on")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("csv")
+                        .long("csv")
+                        .help("export as CSV, short for `--format csv`")
+                        .takes_value(false)
+                        .conflicts_with("format")
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .help("the format to output the report in")
+                        .takes_value(true)
+                        .possible_values(&formats)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("template")
+                        .long("template")
+                        .help("render with your own template, by path or by name from ~/.config/timecop/templates")
+                        .takes_value(true)
+                        .conflicts_with_all(&["format", "csv"])
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .short("o")
+                        .help("write to this file instead of the terminal")
+                        .takes_value(true)
+                        .required(false),
+                ),
+        ))
+        .subcommand(period_args(
+            SubCommand::with_name("invoice")
+                .about("invoice the time spent on this project in a period")
+                .long_about(
+                    "This prices the time spent on every task at its hourly rate (see `timecop rate`) and adds
+tax. Every invoice gets the next invoice number, use `--draft` to check it first without
+using up a number.",
+                )
+                .arg(
+                    Arg::with_name("tax")
+                        .long("tax")
+                        .help("the tax percentage to add (default: timecop.tax from git config, or 0)")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .help("the format to write the invoice in")
+                        .takes_value(true)
+                        .possible_values(&render::invoice::FORMATS)
+                        .default_value("markdown"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .short("o")
+                        .help("write to this file instead of the terminal")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("draft")
+                        .long("draft")
+                        .help("show the invoice without giving it a number")
+                        .takes_value(false)
+                        .required(false),
+                ),
+        ))
+        .subcommand(
+            SubCommand::with_name("rate")
+                .about("show or set the hourly rate for this project or one of its tasks")
+                .arg(
+                    Arg::with_name("amount")
+                        .help("the hourly rate (85, 85.50)")
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("currency")
+                        .long("currency")
+                        .help("the currency to invoice the project in (EUR, USD)")
+                        .takes_value(true)
+                        .conflicts_with("task")
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("task")
+                        .long("task")
+                        .help("set the rate for this task (name or id) instead of the project")
+                        .takes_value(true)
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("clear")
+                        .long("clear")
+                        .help("remove the rate, tasks go back to the rate of the project")
                         .takes_value(false)
+                        .conflicts_with_all(&["amount", "currency"])
+                        .required(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("billable")
+                .about("show or set whether the time spent on this project or one of its tasks is billable")
+                .arg(
+                    Arg::with_name("billable")
+                        .help("whether the time spent is billable, tasks can go back to the project `default`")
+                        .possible_values(&["yes", "no", "default"])
+                        .required(false),
+                )
+                .arg(
+                    Arg::with_name("task")
+                        .long("task")
+                        .help("set it for this task (name or id) instead of the project")
+                        .takes_value(true)
                         .required(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("entry")
+                .about("list, edit or delete existing log entries")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about("list the log entries for this project")
+                        .arg(
+                            Arg::with_name("limit")
+                                .help("only show the most recent entries")
+                                .short("n")
+                                .long("limit")
+                                .takes_value(true)
+                                .required(false),
+                        ),
                 )
+                .subcommand(
+                    SubCommand::with_name("edit")
+                        .about("edit a log entry, without arguments it will prompt for every field")
+                        .arg(
+                            Arg::with_name("id")
+                                .help("the id of the log entry, pick one when omitted")
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::with_name("range")
+                                .help("edit all entries between two dates in your $EDITOR (from..to)")
+                                .long("range")
+                                .takes_value(true)
+                                .conflicts_with_all(&["id", "message", "minutes", "date", "task"])
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::with_name("message")
+                                .help("the new log entry message")
+                                .short("m")
+                                .long("message")
+                                .takes_value(true)
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::with_name("minutes")
+                                .help("the new time spent (90, 1h30, 1.5h)")
+                                .long("minutes")
+                                .takes_value(true)
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::with_name("date")
+                                .help("the new date (yesterday 14:00, 2021-06-25 14:00)")
+                                .long("date")
+                                .takes_value(true)
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::with_name("task")
+                                .help("move the entry to another task (name or id)")
+                                .long("task")
+                                .takes_value(true)
+                                .required(false),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("delete")
+                        .about("delete a log entry")
+                        .arg(
+                            Arg::with_name("id")
+                                .help("the id of the log entry, pick one when omitted")
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::with_name("yes")
+                                .help("do not ask for confirmation")
+                                .short("y")
+                                .long("yes")
+                                .takes_value(false)
+                                .required(false),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("task")
+                .about("manage the tasks of this project")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("estimate")
+                        .about("show or set how long a task is supposed to take")
+                        .arg(
+                            Arg::with_name("hours")
+                                .help("the estimate in hours (8, 1.5) or written out (6h30)")
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::with_name("task")
+                                .help("the task (name or id), the task of the current branch by default")
+                                .long("task")
+                                .takes_value(true)
+                                .required(false),
+                        )
+                        .arg(
+                            Arg::with_name("clear")
+                                .help("remove the estimate")
+                                .long("clear")
+                                .takes_value(false)
+                                .conflicts_with("hours")
+                                .required(false),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("hook")
+                .about("show, install or uninstall the post-commit git hook")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("status")
+                        .about("show where the hook lives and whether timecop is part of it"),
+                )
+                .subcommand(
+                    SubCommand::with_name("install")
+                        .about("add timecop to the hook, next to whatever it already runs"),
+                )
+                .subcommand(
+                    SubCommand::with_name("uninstall")
+                        .about("remove timecop from the hook, leaving the rest as it was"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("start")
+                .about("start a timer for the current task")
+                .long_about(
+                    "This starts a timer for the task of the current branch, when you are done run
+`timecop stop` to turn the measured time into a log entry. Only one timer can be running
+at a time, starting a new one will offer to stop the one that is already running.",
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("stop")
+                .about("stop the running timer and log the time spent")
                 .arg(
-                    Arg::with_name("detailed")
-                        .long("detail")
-                        .help("include task log entries")
+                    Arg::with_name("non-billable")
+                        .help("do not bill the time spent, whatever the task or project says")
+                        .long("non-billable")
                         .takes_value(false)
                         .required(false),
                 ),
-        );
+        )
+        .subcommand(SubCommand::with_name("status").about("show the running timer, if any"))
+        .subcommand(SubCommand::with_name("pause").about("pause the running timer"))
+        .subcommand(SubCommand::with_name("resume").about("resume the paused timer"));
 
     // Get clap matches
     let matches = matches.get_matches();
 
-    // Ensure valid repository
-    utils::ensure_valid_repo();
+    // Ensure valid repository, reports can be run from anywhere
+    if matches.subcommand_name() != Some("report") {
+        utils::ensure_valid_repo();
+    }
 
     // Generate the State
     let mut state = State::new();
@@ -86,3 +476,116 @@ each commit you make how much time you think you spent on it.")
     commands::exec(state)?;
     Ok(())
 }
+
+// The arguments to filter and shape a summary of the time spent
+fn summary_args<'a, 'b>(command: App<'a, 'b>) -> App<'a, 'b> {
+    period_args(command).arg(
+        Arg::with_name("detailed")
+            .long("detail")
+            .help("include task log entries")
+            .takes_value(false)
+            .required(false),
+    )
+}
+
+// The arguments to pick the days to add up, and how to round them
+fn period_args<'a, 'b>(command: App<'a, 'b>) -> App<'a, 'b> {
+    command
+        .arg(
+            Arg::with_name("round")
+                .long("round")
+                .help("round the time spent to a multiple of these minutes (15, 6m, 1h)")
+                .takes_value(true)
+                .required(false),
+        )
+        .arg(
+            Arg::with_name("round-mode")
+                .long("round-mode")
+                .help("round up, down or to the nearest multiple (default: nearest)")
+                .takes_value(true)
+                .possible_values(&["up", "down", "nearest"])
+                .requires("round"),
+        )
+        .arg(
+            Arg::with_name("round-per")
+                .long("round-per")
+                .help("round every log entry, every task per day or every day (default: task)")
+                .takes_value(true)
+                .possible_values(&["entry", "task", "day"])
+                .requires("round"),
+        )
+        .arg(
+            Arg::with_name("billable-only")
+                .long("billable-only")
+                .help("leave out the time that is not billable")
+                .takes_value(false)
+                .required(false),
+        )
+        .arg(
+            Arg::with_name("tz")
+                .long("tz")
+                .help("group the days in this timezone (America/Los_Angeles, -07:00, local)")
+                .takes_value(true)
+                .required(false),
+        )
+        .arg(
+            Arg::with_name("from")
+                .long("from")
+                .help("only include days from this date on (2021-06-01, last monday)")
+                .takes_value(true)
+                .conflicts_with("period")
+                .required(false),
+        )
+        .arg(
+            Arg::with_name("to")
+                .long("to")
+                .help("only include days up to and including this date")
+                .takes_value(true)
+                .conflicts_with("period")
+                .required(false),
+        )
+        .arg(
+            Arg::with_name("today")
+                .long("today")
+                .help("only include today")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("yesterday")
+                .long("yesterday")
+                .help("only include yesterday")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("this-week")
+                .long("this-week")
+                .help("only include this week")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("last-week")
+                .long("last-week")
+                .help("only include last week")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("this-month")
+                .long("this-month")
+                .help("only include this month")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("last-month")
+                .long("last-month")
+                .help("only include last month")
+                .takes_value(false),
+        )
+        .group(ArgGroup::with_name("period").args(&[
+            "today",
+            "yesterday",
+            "this-week",
+            "last-week",
+            "this-month",
+            "last-month",
+        ]))
+}
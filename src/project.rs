@@ -1,4 +1,5 @@
 use crate::database;
+use crate::entry::Entry;
 use crate::summary;
 use crate::task::Task;
 use crate::utils;
@@ -6,12 +7,17 @@ use crate::utils;
 use chrono::NaiveDateTime;
 use std::vec::Vec;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Project {
     pub id: usize,
     pub name: String,
     pub inserted_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    // The hourly rate in cents, tasks can override it
+    pub rate: Option<i64>,
+    pub currency: Option<String>,
+    // Whether the time spent is billable, tasks can override it
+    pub billable: bool,
 }
 
 impl Project {
@@ -31,10 +37,26 @@ impl Project {
         list_all_projects()
     }
 
+    pub fn find(name_or_id: &str) -> Option<Project> {
+        find_project(name_or_id)
+    }
+
     pub fn list_tasks(&self) -> Vec<Task> {
         Task::list_for(&self)
     }
 
+    pub fn find_task(&self, name_or_id: &str) -> Option<Task> {
+        find_project_task(self, name_or_id)
+    }
+
+    pub fn find_task_by_name(&self, name: &str) -> Option<Task> {
+        find_project_task_by_name(self, name)
+    }
+
+    pub fn list_entries(&self) -> Vec<Entry> {
+        Entry::list_for_project(self)
+    }
+
     pub fn add_task(&self, name: String) -> Option<Task> {
         Task::add_to(&self, name)
     }
@@ -43,8 +65,16 @@ impl Project {
         save_context(&self, remote)
     }
 
-    pub fn summary(&self) -> summary::Summary {
-        summary::for_project(&self)
+    pub fn summary(&self, options: &summary::SummaryOptions) -> summary::Summary {
+        summary::for_project(self, options)
+    }
+
+    pub fn set_rate(&self, rate: Option<i64>, currency: Option<String>) {
+        save_rate(self, rate, currency)
+    }
+
+    pub fn set_billable(&self, billable: bool) {
+        save_billable(self, billable)
     }
 
     pub fn touch(&self) {
@@ -54,7 +84,7 @@ impl Project {
 
 fn find_existing_project() -> Option<Project> {
     // Is the current path a Git repository?
-    let repo = match utils::get_current_repo() {
+    let repo = match utils::get_context_repo() {
         Some(repo) => repo,
         None => return None,
     };
@@ -69,6 +99,44 @@ fn find_existing_project() -> Option<Project> {
     get_by_remote(remote)
 }
 
+// Look up a project either by its id, or by its name
+fn find_project(name_or_id: &str) -> Option<Project> {
+    let mut projects = Project::list();
+
+    if let Ok(id) = name_or_id.parse::<usize>() {
+        if let Some(index) = projects.iter().position(|p| p.id == id) {
+            return Some(projects.remove(index));
+        }
+    }
+
+    projects
+        .into_iter()
+        .find(|p| p.name.eq_ignore_ascii_case(name_or_id.trim()))
+}
+
+// Look up a task of this project either by its id, or by its name
+fn find_project_task(project: &Project, name_or_id: &str) -> Option<Task> {
+    let mut tasks = project.list_tasks();
+
+    if let Ok(id) = name_or_id.parse::<usize>() {
+        if let Some(index) = tasks.iter().position(|t| t.id == id) {
+            return Some(tasks.remove(index));
+        }
+    }
+
+    tasks
+        .into_iter()
+        .find(|t| t.name.eq_ignore_ascii_case(name_or_id.trim()))
+}
+
+// A task named after the id of another task is still found by its name
+fn find_project_task_by_name(project: &Project, name: &str) -> Option<Task> {
+    project
+        .list_tasks()
+        .into_iter()
+        .find(|t| t.name.eq_ignore_ascii_case(name.trim()))
+}
+
 // This function will attempt to create a new project and then
 // it will return said Project (or None if something goes wrong)
 fn create_project(remote: String, name: String) -> Option<Project> {
@@ -76,7 +144,9 @@ fn create_project(remote: String, name: String) -> Option<Project> {
 
     database::with_db(|db| {
         let mut cursor = db
-            .prepare("REPLACE INTO projects VALUES (null, ?, DATETIME(), DATETIME());")
+            .prepare(
+                "REPLACE INTO projects (name, inserted_at, updated_at) VALUES (?, DATETIME(), DATETIME());",
+            )
             .unwrap()
             .into_cursor();
 
@@ -88,7 +158,7 @@ fn create_project(remote: String, name: String) -> Option<Project> {
             cursor = db
                 .prepare(
                     "
-                SELECT p.id, p.name, p.inserted_at, p.updated_at
+                SELECT p.id, p.name, p.inserted_at, p.updated_at, p.rate, p.currency, p.billable
                 FROM projects p
                 WHERE p.id IN(SELECT last_insert_rowid());
                 ",
@@ -128,6 +198,48 @@ fn save_context(project: &Project, remote: String) {
     });
 }
 
+// Store the hourly rate (in cents) and the currency to invoice in
+fn save_rate(project: &Project, rate: Option<i64>, currency: Option<String>) {
+    database::with_db(|db| {
+        let mut cursor = db
+            .prepare(
+                "UPDATE projects SET rate = ?, currency = ?, updated_at = DATETIME() WHERE id = ?;",
+            )
+            .unwrap()
+            .into_cursor();
+
+        cursor
+            .bind(&[
+                rate.map_or(sqlite::Value::Null, sqlite::Value::Integer),
+                currency
+                    .clone()
+                    .map_or(sqlite::Value::Null, sqlite::Value::String),
+                sqlite::Value::Integer(project.id as i64),
+            ])
+            .unwrap();
+
+        cursor.next().unwrap();
+    });
+}
+
+fn save_billable(project: &Project, billable: bool) {
+    database::with_db(|db| {
+        let mut cursor = db
+            .prepare("UPDATE projects SET billable = ?, updated_at = DATETIME() WHERE id = ?;")
+            .unwrap()
+            .into_cursor();
+
+        cursor
+            .bind(&[
+                sqlite::Value::Integer(billable as i64),
+                sqlite::Value::Integer(project.id as i64),
+            ])
+            .unwrap();
+
+        cursor.next().unwrap();
+    });
+}
+
 // This function will "touch" the project, updating it's "last updated" timestamp
 // Which should result in more usable sorted projects and tasks in the UI.
 fn touch_project(project: &Project) {
@@ -152,7 +264,7 @@ fn get_by_id(id: usize) -> Option<Project> {
         let mut cursor = db
             .prepare(
                 "
-                SELECT p.id, p.name, p.inserted_at, p.updated_at
+                SELECT p.id, p.name, p.inserted_at, p.updated_at, p.rate, p.currency, p.billable
                 FROM projects p
                 WHERE p.id = ?;",
             )
@@ -174,7 +286,7 @@ fn get_by_remote(remote: String) -> Option<Project> {
         let mut cursor = db
             .prepare(
                 "
-                SELECT p.id, p.name, p.inserted_at, p.updated_at
+                SELECT p.id, p.name, p.inserted_at, p.updated_at, p.rate, p.currency, p.billable
                 FROM contexts c
                 LEFT JOIN projects p ON p.id = c.project_id
                 WHERE c.context = ?
@@ -200,7 +312,7 @@ fn list_all_projects() -> Vec<Project> {
         let cursor = db
             .prepare(
                 "
-                SELECT p.id, p.name, p.inserted_at, p.updated_at
+                SELECT p.id, p.name, p.inserted_at, p.updated_at, p.rate, p.currency, p.billable
                 FROM projects p
                 ORDER BY p.updated_at DESC;",
             )
@@ -233,5 +345,8 @@ fn row_to_project(row: Result<Option<&[sqlite::Value]>, sqlite::Error>) -> Optio
         name: columns[1].as_string().unwrap().to_string(),
         inserted_at: utils::sql_to_datetime(columns[2].as_string()).unwrap(),
         updated_at: utils::sql_to_datetime(columns[3].as_string()).unwrap(),
+        rate: columns[4].as_integer(),
+        currency: columns[5].as_string().map(|c| c.to_string()),
+        billable: columns[6].as_integer() != Some(0),
     })
 }
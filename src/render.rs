@@ -0,0 +1,104 @@
+pub mod budget;
+pub mod csv;
+pub mod html;
+pub mod invoice;
+pub mod json;
+pub mod markdown;
+pub mod template;
+pub mod terminal;
+
+use crate::dates::DateRange;
+use crate::summary::{CombinedSummary, Rounding, Summary};
+
+use chrono::Weekday;
+use std::io;
+
+// How the days of a summary are rolled up before rendering
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rollup {
+    Day,
+    Week,
+    Month,
+}
+
+impl Rollup {
+    pub fn parse(input: &str) -> Option<Rollup> {
+        match input {
+            "day" => Some(Rollup::Day),
+            "week" => Some(Rollup::Week),
+            "month" => Some(Rollup::Month),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rollup::Day => "day",
+            Rollup::Week => "week",
+            Rollup::Month => "month",
+        }
+    }
+}
+
+// Everything a renderer needs to know besides the summary itself
+#[derive(Debug, Clone, Copy)]
+pub struct RenderOptions {
+    pub detailed: bool,
+    pub range: DateRange,
+    pub rounding: Option<Rounding>,
+    pub by: Rollup,
+    pub week_start: Weekday,
+    // Escape codes only make sense when writing to a terminal
+    pub color: bool,
+}
+
+// A way to turn a summary into something to read, or to process further.
+// Add new formats to `renderers` below to make them available to `--format`.
+pub trait ReportRenderer {
+    // The name to pick this renderer with `--format`
+    fn name(&self) -> &'static str;
+
+    // Not every format makes sense for every rollup
+    fn supports(&self, by: Rollup) -> bool {
+        by == Rollup::Day
+    }
+
+    fn render(
+        &self,
+        summary: &Summary,
+        options: &RenderOptions,
+        out: &mut dyn io::Write,
+    ) -> io::Result<()>;
+
+    // Several projects at once (see `timecop report`), always by day
+    fn render_combined(
+        &self,
+        _summary: &CombinedSummary,
+        _options: &RenderOptions,
+        _out: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        Err(io::Error::other(format!(
+            "The {} output can only show a single project",
+            self.name()
+        )))
+    }
+}
+
+pub fn renderers() -> Vec<Box<dyn ReportRenderer>> {
+    vec![
+        Box::new(terminal::TerminalRenderer),
+        Box::new(csv::CsvRenderer),
+        Box::new(json::JsonRenderer),
+        Box::new(json::NdjsonRenderer),
+        Box::new(markdown::MarkdownRenderer),
+        Box::new(html::HtmlRenderer),
+    ]
+}
+
+pub fn find(name: &str) -> Option<Box<dyn ReportRenderer>> {
+    renderers().into_iter().find(|r| r.name() == name)
+}
+
+pub fn names() -> Vec<&'static str> {
+    renderers().iter().map(|r| r.name()).collect()
+}
@@ -0,0 +1,117 @@
+use super::terminal::{self, Styles};
+use super::{RenderOptions, ReportRenderer, Rollup};
+use crate::summary::Summary;
+use crate::utils;
+
+use std::io;
+
+// The estimate of every task next to the time actually spent on it, to
+// calibrate the next estimates with. Tasks without an estimate are left out,
+// and only the time spent within the range counts.
+pub struct BudgetRenderer {
+    pub csv: bool,
+    pub estimates: Vec<TaskEstimate>,
+}
+
+// The estimate of a task, the time spent on it comes from the summary
+pub struct TaskEstimate {
+    pub id: usize,
+    pub name: String,
+    pub minutes: usize,
+}
+
+impl ReportRenderer for BudgetRenderer {
+    fn name(&self) -> &'static str {
+        "budget"
+    }
+
+    fn supports(&self, _by: Rollup) -> bool {
+        true
+    }
+
+    fn render(
+        &self,
+        summary: &Summary,
+        options: &RenderOptions,
+        out: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        let spent = summary.tasks();
+        let tasks: Vec<(String, usize, usize)> = self
+            .estimates
+            .iter()
+            .map(|estimate| {
+                let minutes = spent
+                    .iter()
+                    .filter(|t| t.id == estimate.id)
+                    .map(|t| t.minutes)
+                    .sum();
+                (estimate.name.to_string(), estimate.minutes, minutes)
+            })
+            .collect();
+
+        if self.csv {
+            write_csv(&tasks, out)
+        } else {
+            write_text(summary, &tasks, options, out)
+        }
+    }
+}
+
+fn write_text(
+    summary: &Summary,
+    tasks: &[(String, usize, usize)],
+    options: &RenderOptions,
+    out: &mut dyn io::Write,
+) -> io::Result<()> {
+    let styles = Styles::new(options.color);
+
+    terminal::write_title(out, &styles, "Budget:", &summary.name)?;
+    if options.range.is_bounded() {
+        terminal::write_title(out, &styles, "Period:", &options.range.describe())?;
+    }
+    writeln!(out)?;
+
+    if tasks.is_empty() {
+        writeln!(
+            out,
+            "None of the tasks have an estimate yet, set one with `timecop task estimate <hours>`."
+        )?;
+    }
+
+    for (name, estimate, minutes) in tasks {
+        writeln!(
+            out,
+            "  {}{}{}{} {}",
+            styles.white,
+            styles.bold,
+            name,
+            styles.reset,
+            utils::format_budget(*minutes, *estimate)
+        )?;
+    }
+
+    Ok(())
+}
+
+// What is left is negative when the task ran over its estimate
+fn write_csv(tasks: &[(String, usize, usize)], out: &mut dyn io::Write) -> io::Result<()> {
+    let mut wtr = ::csv::Writer::from_writer(out);
+
+    wtr.write_record([
+        "Task",
+        "Estimate (Minutes)",
+        "Time Spent (Minutes)",
+        "Left (Minutes)",
+    ])?;
+
+    for (name, estimate, minutes) in tasks {
+        wtr.write_record([
+            name,
+            &format!("{}", estimate),
+            &format!("{}", minutes),
+            &format!("{}", *estimate as i64 - *minutes as i64),
+        ])?;
+    }
+
+    wtr.flush()
+}
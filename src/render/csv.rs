@@ -0,0 +1,238 @@
+use super::{RenderOptions, ReportRenderer, Rollup};
+use crate::summary::{CombinedSummary, SummarizedTask, Summary};
+
+use std::io;
+
+// One row per task (or log entry), to process with whatever tool you have.
+// When rounding, the raw minutes get a column of their own.
+pub struct CsvRenderer;
+
+impl ReportRenderer for CsvRenderer {
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+
+    fn supports(&self, _by: Rollup) -> bool {
+        true
+    }
+
+    fn render(
+        &self,
+        summary: &Summary,
+        options: &RenderOptions,
+        out: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        let mut wtr = ::csv::Writer::from_writer(out);
+
+        match options.by {
+            Rollup::Day => write_days(&mut wtr, summary, options)?,
+            Rollup::Week => write_weeks(&mut wtr, summary, options)?,
+            Rollup::Month => write_months(&mut wtr, summary, options)?,
+        }
+
+        wtr.flush()
+    }
+
+    // The same columns as a single project by day, one project after another
+    fn render_combined(
+        &self,
+        summary: &CombinedSummary,
+        options: &RenderOptions,
+        out: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        let mut wtr = ::csv::Writer::from_writer(out);
+        wtr.write_record(headers(&["Project", "Date"], options))?;
+
+        for day in summary.days.iter() {
+            let date = format!("{}", day.date.format("%Y-%m-%d"));
+
+            for project in day.projects.iter() {
+                let period = vec![project.name.to_string(), date.to_string()];
+                write_tasks(&mut wtr, &period, &project.tasks, options)?;
+            }
+        }
+
+        wtr.flush()
+    }
+}
+
+fn headers<'a>(period: &[&'a str], options: &RenderOptions) -> Vec<&'a str> {
+    let mut headers: Vec<&str> = period.to_vec();
+    headers.push("Time Spent (Minutes)");
+
+    if options.rounding.is_some() {
+        headers.push("Raw Time Spent (Minutes)");
+    }
+
+    headers.push("Task");
+
+    if options.detailed {
+        headers.push("Log Entry");
+    }
+
+    headers.push("Billable");
+    headers
+}
+
+fn write_days<W: io::Write>(
+    wtr: &mut ::csv::Writer<W>,
+    summary: &Summary,
+    options: &RenderOptions,
+) -> io::Result<()> {
+    // Write our headers first
+    wtr.write_record(headers(&["Project", "Date"], options))?;
+
+    // Write all the records
+    for day in summary.days.iter() {
+        let period = vec![
+            summary.name.to_string(),
+            format!("{}", day.date.format("%Y-%m-%d")),
+        ];
+
+        write_tasks(wtr, &period, &day.tasks, options)?;
+
+        // The total is what gets rounded when rounding per day
+        if options.rounding.is_some() {
+            write_row(
+                wtr,
+                &period,
+                day.minutes,
+                day.raw_minutes,
+                None,
+                None,
+                options,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+// Every task of a week, followed by a total for the week (without a task)
+fn write_weeks<W: io::Write>(
+    wtr: &mut ::csv::Writer<W>,
+    summary: &Summary,
+    options: &RenderOptions,
+) -> io::Result<()> {
+    let period_headers = ["Project", "Year", "Week", "From", "To"];
+    wtr.write_record(headers(&period_headers, options))?;
+
+    for week in summary.weeks(options.week_start, options.range) {
+        let period = vec![
+            summary.name.to_string(),
+            format!("{}", week.year),
+            format!("{}", week.week),
+            format!("{}", week.from.format("%Y-%m-%d")),
+            format!("{}", week.to.format("%Y-%m-%d")),
+        ];
+
+        write_tasks(wtr, &period, &week.tasks, options)?;
+        write_row(
+            wtr,
+            &period,
+            week.minutes,
+            week.raw_minutes,
+            None,
+            None,
+            options,
+        )?;
+    }
+
+    Ok(())
+}
+
+// Every task of a month, followed by a total for the month (without a task)
+fn write_months<W: io::Write>(
+    wtr: &mut ::csv::Writer<W>,
+    summary: &Summary,
+    options: &RenderOptions,
+) -> io::Result<()> {
+    wtr.write_record(headers(&["Project", "Month"], options))?;
+
+    for month in summary.months() {
+        let period = vec![
+            summary.name.to_string(),
+            format!("{}", month.first_day().format("%Y-%m")),
+        ];
+
+        write_tasks(wtr, &period, &month.tasks, options)?;
+        write_row(
+            wtr,
+            &period,
+            month.minutes,
+            month.raw_minutes,
+            None,
+            None,
+            options,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn write_tasks<W: io::Write>(
+    wtr: &mut ::csv::Writer<W>,
+    period: &[String],
+    tasks: &[SummarizedTask],
+    options: &RenderOptions,
+) -> io::Result<()> {
+    for task in tasks {
+        if options.detailed {
+            for entry in task.entries.iter() {
+                write_row(
+                    wtr,
+                    period,
+                    entry.minutes,
+                    entry.raw_minutes,
+                    Some(task),
+                    Some(&entry.name),
+                    options,
+                )?;
+            }
+        } else {
+            write_row(
+                wtr,
+                period,
+                task.minutes,
+                task.raw_minutes,
+                Some(task),
+                None,
+                options,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_row<W: io::Write>(
+    wtr: &mut ::csv::Writer<W>,
+    period: &[String],
+    minutes: usize,
+    raw_minutes: usize,
+    task: Option<&SummarizedTask>,
+    entry: Option<&str>,
+    options: &RenderOptions,
+) -> io::Result<()> {
+    let mut record = period.to_vec();
+    record.push(format!("{}", minutes));
+
+    if options.rounding.is_some() {
+        record.push(format!("{}", raw_minutes));
+    }
+
+    record.push(task.map_or("".to_string(), |t| t.name.to_string()));
+
+    if options.detailed {
+        record.push(entry.unwrap_or("").to_string());
+    }
+
+    record.push(match task {
+        Some(task) if task.billable => "yes".to_string(),
+        Some(_) => "no".to_string(),
+        None => "".to_string(),
+    });
+
+    wtr.write_record(&record)?;
+    Ok(())
+}
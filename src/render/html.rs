@@ -0,0 +1,193 @@
+use super::{RenderOptions, ReportRenderer};
+use crate::summary::{CombinedSummary, SummarizedTask, Summary};
+use crate::utils;
+
+use std::io;
+
+const STYLE: &str = "
+  body { font-family: -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; color: #24292e; max-width: 50em; margin: 2em auto; padding: 0 1em; }
+  h1 { margin-bottom: 0.2em; }
+  .period { color: #6a737d; margin-top: 0; }
+  h2 { font-size: 1.1em; margin-top: 2em; }
+  table { border-collapse: collapse; width: 100%; }
+  th, td { text-align: left; padding: 0.4em 0.6em; border-bottom: 1px solid #e1e4e8; }
+  th { background: #f6f8fa; }
+  td.time, th.time { text-align: right; white-space: nowrap; font-variant-numeric: tabular-nums; }
+  tr.entry td { color: #586069; }
+  tr.entry td:first-child { padding-left: 2em; }
+  tr.project td { font-weight: bold; background: #f6f8fa; }
+  tr.total td { font-weight: bold; border-bottom: none; }
+  .raw { color: #6a737d; font-weight: normal; }
+  .non-billable { color: #6a737d; font-style: italic; }
+";
+
+pub struct HtmlRenderer;
+
+impl ReportRenderer for HtmlRenderer {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+
+    fn render(
+        &self,
+        summary: &Summary,
+        options: &RenderOptions,
+        out: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        let document = to_html(summary, options);
+        out.write_all(document.as_bytes())
+    }
+
+    fn render_combined(
+        &self,
+        summary: &CombinedSummary,
+        options: &RenderOptions,
+        out: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        let document = combined_to_html(summary, options);
+        out.write_all(document.as_bytes())
+    }
+}
+
+// A single file without any outside dependencies, a table for every day
+fn to_html(summary: &Summary, options: &RenderOptions) -> String {
+    let mut out = header(&summary.name, options);
+
+    if summary.days.is_empty() {
+        out.push_str("<p>Nothing was logged in this period.</p>\n");
+    }
+
+    for day in summary.days.iter() {
+        out.push_str(&format!(
+            "<h2>{}</h2>\n<table>\n<tr><th>Task</th><th class=\"time\">Time</th></tr>\n",
+            day.date.format("%A %-e %B, %Y")
+        ));
+
+        push_tasks(&mut out, &day.tasks, options);
+
+        out.push_str(&format!(
+            "<tr class=\"total\"><td>Total</td><td class=\"time\">{}</td></tr>\n</table>\n",
+            format_minutes(day.minutes, day.raw_minutes)
+        ));
+    }
+
+    let non_billable = summary.non_billable_minutes();
+    if non_billable > 0 {
+        out.push_str(&format!(
+            "<p>Billable: {}<br>Non-billable: {}</p>\n",
+            utils::format_time(summary.billable_minutes()),
+            utils::format_time(non_billable)
+        ));
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+// The projects of a day share its table, every project in a row of its own
+fn combined_to_html(summary: &CombinedSummary, options: &RenderOptions) -> String {
+    let mut out = header(&summary.name, options);
+
+    if summary.days.is_empty() {
+        out.push_str("<p>Nothing was logged in this period.</p>\n");
+    }
+
+    for day in summary.days.iter() {
+        out.push_str(&format!(
+            "<h2>{}</h2>\n<table>\n<tr><th>Task</th><th class=\"time\">Time</th></tr>\n",
+            day.date.format("%A %-e %B, %Y")
+        ));
+
+        for project in day.projects.iter() {
+            out.push_str(&format!(
+                "<tr class=\"project\"><td>{}</td><td class=\"time\">{}</td></tr>\n",
+                escape(&project.name),
+                format_minutes(project.minutes, project.raw_minutes)
+            ));
+
+            push_tasks(&mut out, &project.tasks, options);
+        }
+
+        out.push_str(&format!(
+            "<tr class=\"total\"><td>Total</td><td class=\"time\">{}</td></tr>\n</table>\n",
+            format_minutes(day.minutes, day.raw_minutes)
+        ));
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+// Everything up to the first day: the document head, the title and the period
+fn header(name: &str, options: &RenderOptions) -> String {
+    let mut out = String::new();
+
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str(&format!("<title>{}</title>\n", escape(name)));
+    out.push_str(&format!("<style>{}</style>\n", STYLE));
+    out.push_str("</head>\n<body>\n");
+    out.push_str(&format!("<h1>{}</h1>\n", escape(name)));
+
+    if options.range.is_bounded() {
+        out.push_str(&format!(
+            "<p class=\"period\">{}</p>\n",
+            escape(&options.range.describe())
+        ));
+    }
+
+    if let Some(rounding) = options.rounding {
+        out.push_str(&format!(
+            "<p class=\"period\">Rounded {}</p>\n",
+            escape(&rounding.describe())
+        ));
+    }
+
+    out
+}
+
+fn push_tasks(out: &mut String, tasks: &[SummarizedTask], options: &RenderOptions) {
+    for task in tasks {
+        out.push_str(&format!(
+            "<tr class=\"task\"><td>{}{}</td><td class=\"time\">{}</td></tr>\n",
+            escape(&task.name),
+            if task.billable {
+                ""
+            } else {
+                " <span class=\"non-billable\">non-billable</span>"
+            },
+            format_minutes(task.minutes, task.raw_minutes)
+        ));
+
+        if options.detailed {
+            for entry in task.entries.iter() {
+                out.push_str(&format!(
+                    "<tr class=\"entry\"><td>{}</td><td class=\"time\">{}</td></tr>\n",
+                    escape(&entry.name),
+                    format_minutes(entry.minutes, entry.raw_minutes)
+                ));
+            }
+        }
+    }
+}
+
+// The time spent, along with the time logged when rounding changed it
+fn format_minutes(minutes: usize, raw_minutes: usize) -> String {
+    if minutes != raw_minutes {
+        format!(
+            "{} <span class=\"raw\">(raw {})</span>",
+            utils::format_time(minutes),
+            utils::format_time(raw_minutes)
+        )
+    } else {
+        utils::format_time(minutes)
+    }
+}
+
+pub fn escape(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
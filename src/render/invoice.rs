@@ -0,0 +1,191 @@
+use super::{html, markdown};
+use crate::invoice::{self, Invoice, InvoiceLine};
+
+use std::io;
+
+// The formats an invoice can be written in
+pub const FORMATS: [&str; 3] = ["markdown", "html", "csv"];
+
+const STYLE: &str = "
+  body { font-family: -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; color: #24292e; max-width: 50em; margin: 2em auto; padding: 0 1em; }
+  h1 { margin-bottom: 0.2em; }
+  .details { color: #6a737d; margin-top: 0; }
+  table { border-collapse: collapse; width: 100%; margin-top: 2em; }
+  th, td { text-align: left; padding: 0.4em 0.6em; border-bottom: 1px solid #e1e4e8; }
+  th { background: #f6f8fa; }
+  td.amount, th.amount { text-align: right; white-space: nowrap; font-variant-numeric: tabular-nums; }
+  tr.sum td { border-bottom: none; }
+  tr.total td { font-weight: bold; border-bottom: none; }
+";
+
+pub fn render(invoice: &Invoice, format: &str, out: &mut dyn io::Write) -> io::Result<()> {
+    match format {
+        "html" => out.write_all(to_html(invoice).as_bytes()),
+        "csv" => write_csv(invoice, out),
+        _ => out.write_all(to_markdown(invoice).as_bytes()),
+    }
+}
+
+fn title(invoice: &Invoice) -> String {
+    match invoice.number {
+        Some(number) => format!("Invoice {}", number),
+        None => "Invoice (draft)".to_string(),
+    }
+}
+
+// An amount along with its currency, when the project has one
+fn format_money(invoice: &Invoice, cents: i64) -> String {
+    match &invoice.currency {
+        Some(currency) => format!("{} {}", invoice::format_amount(cents), currency),
+        None => invoice::format_amount(cents),
+    }
+}
+
+// Non-billable lines say so instead of showing a rate
+fn format_rate(invoice: &Invoice, line: &InvoiceLine) -> String {
+    if line.billable {
+        format_money(invoice, line.rate)
+    } else {
+        "non-billable".to_string()
+    }
+}
+
+fn tax_label(invoice: &Invoice) -> String {
+    format!("Tax ({}%)", invoice.tax_percentage)
+}
+
+fn to_markdown(invoice: &Invoice) -> String {
+    let mut out = format!("# {}\n\n", title(invoice));
+
+    out.push_str(&format!(
+        "**Project:** {}  \n",
+        markdown::escape(&invoice.project)
+    ));
+    out.push_str(&format!("**Period:** {}  \n", invoice.range.describe()));
+    out.push_str(&format!(
+        "**Date:** {}\n\n",
+        invoice.date.format("%-e %B, %Y")
+    ));
+
+    out.push_str("| Task | Hours | Rate | Amount |\n");
+    out.push_str("| --- | ---: | ---: | ---: |\n");
+
+    for line in invoice.lines.iter() {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            markdown::escape(&line.task),
+            invoice::format_hours(line.minutes),
+            format_rate(invoice, line),
+            format_money(invoice, line.amount)
+        ));
+    }
+
+    out.push_str(&format!(
+        "| Subtotal | | | {} |\n",
+        format_money(invoice, invoice.subtotal)
+    ));
+    out.push_str(&format!(
+        "| {} | | | {} |\n",
+        tax_label(invoice),
+        format_money(invoice, invoice.tax)
+    ));
+    out.push_str(&format!(
+        "| **Total** | | | **{}** |\n",
+        format_money(invoice, invoice.total)
+    ));
+
+    out
+}
+
+// A single file without any outside dependencies, ready to print or mail
+fn to_html(invoice: &Invoice) -> String {
+    let mut out = String::new();
+    let title = title(invoice);
+
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str(&format!("<title>{}</title>\n", html::escape(&title)));
+    out.push_str(&format!("<style>{}</style>\n", STYLE));
+    out.push_str("</head>\n<body>\n");
+    out.push_str(&format!("<h1>{}</h1>\n", html::escape(&title)));
+    out.push_str(&format!(
+        "<p class=\"details\">{}<br>{}<br>{}</p>\n",
+        html::escape(&invoice.project),
+        html::escape(&invoice.range.describe()),
+        invoice.date.format("%-e %B, %Y")
+    ));
+
+    out.push_str("<table>\n<tr><th>Task</th><th class=\"amount\">Hours</th><th class=\"amount\">Rate</th><th class=\"amount\">Amount</th></tr>\n");
+
+    for line in invoice.lines.iter() {
+        out.push_str(&format!(
+            "<tr><td>{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td><td class=\"amount\">{}</td></tr>\n",
+            html::escape(&line.task),
+            invoice::format_hours(line.minutes),
+            html::escape(&format_rate(invoice, line)),
+            html::escape(&format_money(invoice, line.amount))
+        ));
+    }
+
+    let sums = [
+        ("sum", "Subtotal".to_string(), invoice.subtotal),
+        ("sum", tax_label(invoice), invoice.tax),
+        ("total", "Total".to_string(), invoice.total),
+    ];
+
+    for (class, label, cents) in sums.iter() {
+        out.push_str(&format!(
+            "<tr class=\"{}\"><td colspan=\"3\">{}</td><td class=\"amount\">{}</td></tr>\n",
+            class,
+            html::escape(label),
+            html::escape(&format_money(invoice, *cents))
+        ));
+    }
+
+    out.push_str("</table>\n</body>\n</html>\n");
+    out
+}
+
+// A row per task followed by the subtotal, tax and total (without hours)
+fn write_csv(invoice: &Invoice, out: &mut dyn io::Write) -> io::Result<()> {
+    let mut wtr = ::csv::Writer::from_writer(out);
+    let number = invoice.number.map_or("".to_string(), |n| n.to_string());
+    let currency = invoice.currency.clone().unwrap_or_default();
+
+    wtr.write_record(["Invoice", "Item", "Hours", "Rate", "Amount", "Currency"])?;
+
+    for line in invoice.lines.iter() {
+        let rate = if line.billable {
+            invoice::format_amount(line.rate)
+        } else {
+            "non-billable".to_string()
+        };
+
+        wtr.write_record([
+            &number,
+            &line.task,
+            &invoice::format_hours(line.minutes),
+            &rate,
+            &invoice::format_amount(line.amount),
+            &currency,
+        ])?;
+    }
+
+    let sums = [
+        ("Subtotal".to_string(), invoice.subtotal),
+        (tax_label(invoice), invoice.tax),
+        ("Total".to_string(), invoice.total),
+    ];
+
+    for (label, cents) in sums.iter() {
+        wtr.write_record([
+            number.as_str(),
+            label,
+            "",
+            "",
+            &invoice::format_amount(*cents),
+            &currency,
+        ])?;
+    }
+
+    wtr.flush()
+}
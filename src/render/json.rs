@@ -0,0 +1,144 @@
+use super::{RenderOptions, ReportRenderer, Rollup};
+use crate::summary::{CombinedSummary, SummarizedMonth, SummarizedWeek, Summary};
+
+use serde::Serialize;
+use std::io;
+
+// The whole summary as one JSON document, always including the entries
+pub struct JsonRenderer;
+
+// One JSON document per period (day, week or month), one per line
+pub struct NdjsonRenderer;
+
+#[derive(Serialize)]
+struct Weeks<'a> {
+    id: usize,
+    name: &'a str,
+    weeks: Vec<SummarizedWeek>,
+}
+
+#[derive(Serialize)]
+struct Months<'a> {
+    id: usize,
+    name: &'a str,
+    months: Vec<SummarizedMonth>,
+}
+
+// A single period on a line of its own, along with its project
+#[derive(Serialize)]
+struct ProjectPeriod<'a, T: Serialize> {
+    project_id: usize,
+    project: &'a str,
+    #[serde(flatten)]
+    period: &'a T,
+}
+
+impl ReportRenderer for JsonRenderer {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn supports(&self, _by: Rollup) -> bool {
+        true
+    }
+
+    fn render(
+        &self,
+        summary: &Summary,
+        options: &RenderOptions,
+        out: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        match options.by {
+            Rollup::Week => serde_json::to_writer_pretty(
+                &mut *out,
+                &Weeks {
+                    id: summary.id,
+                    name: &summary.name,
+                    weeks: summary.weeks(options.week_start, options.range),
+                },
+            ),
+            Rollup::Month => serde_json::to_writer_pretty(
+                &mut *out,
+                &Months {
+                    id: summary.id,
+                    name: &summary.name,
+                    months: summary.months(),
+                },
+            ),
+            Rollup::Day => serde_json::to_writer_pretty(&mut *out, summary),
+        }?;
+
+        writeln!(out)
+    }
+
+    fn render_combined(
+        &self,
+        summary: &CombinedSummary,
+        _options: &RenderOptions,
+        out: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *out, summary)?;
+        writeln!(out)
+    }
+}
+
+impl ReportRenderer for NdjsonRenderer {
+    fn name(&self) -> &'static str {
+        "ndjson"
+    }
+
+    fn supports(&self, _by: Rollup) -> bool {
+        true
+    }
+
+    fn render(
+        &self,
+        summary: &Summary,
+        options: &RenderOptions,
+        out: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        match options.by {
+            Rollup::Week => write_lines(
+                out,
+                summary,
+                &summary.weeks(options.week_start, options.range),
+            ),
+            Rollup::Month => write_lines(out, summary, &summary.months()),
+            Rollup::Day => write_lines(out, summary, &summary.days),
+        }
+    }
+
+    // A line per day, with the projects of that day in it
+    fn render_combined(
+        &self,
+        summary: &CombinedSummary,
+        _options: &RenderOptions,
+        out: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        for day in summary.days.iter() {
+            serde_json::to_writer(&mut *out, day)?;
+            writeln!(out)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_lines<T: Serialize>(
+    out: &mut dyn io::Write,
+    summary: &Summary,
+    periods: &[T],
+) -> io::Result<()> {
+    for period in periods {
+        let line = ProjectPeriod {
+            project_id: summary.id,
+            project: &summary.name,
+            period,
+        };
+
+        serde_json::to_writer(&mut *out, &line)?;
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use super::{RenderOptions, ReportRenderer};
+use crate::summary::{CombinedSummary, SummarizedTask, Summary};
+use crate::utils;
+
+use std::io;
+
+pub struct MarkdownRenderer;
+
+impl ReportRenderer for MarkdownRenderer {
+    fn name(&self) -> &'static str {
+        "markdown"
+    }
+
+    fn render(
+        &self,
+        summary: &Summary,
+        options: &RenderOptions,
+        out: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        let document = to_markdown(summary, options);
+        out.write_all(document.as_bytes())
+    }
+
+    fn render_combined(
+        &self,
+        summary: &CombinedSummary,
+        options: &RenderOptions,
+        out: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        let document = combined_to_markdown(summary, options);
+        out.write_all(document.as_bytes())
+    }
+}
+
+// A heading per day and a bullet per task, with the log entries nested
+// below their task when detailed. Ready to paste into a PR or an email.
+fn to_markdown(summary: &Summary, options: &RenderOptions) -> String {
+    let mut out = header(&summary.name, options);
+
+    if summary.days.is_empty() {
+        out.push_str("Nothing was logged in this period.\n");
+    }
+
+    for day in summary.days.iter() {
+        out.push_str(&format!(
+            "## {} ({})\n\n",
+            day.date.format("%A %-e %B, %Y"),
+            format_minutes(day.minutes, day.raw_minutes)
+        ));
+
+        push_tasks(&mut out, &day.tasks, options);
+        out.push('\n');
+    }
+
+    let non_billable = summary.non_billable_minutes();
+    if non_billable > 0 {
+        out.push_str(&format!(
+            "**Billable:** {}  \n**Non-billable:** {}\n",
+            utils::format_time(summary.billable_minutes()),
+            utils::format_time(non_billable)
+        ));
+    }
+
+    out
+}
+
+// Every day gets a heading, with a heading for every project below it
+fn combined_to_markdown(summary: &CombinedSummary, options: &RenderOptions) -> String {
+    let mut out = header(&summary.name, options);
+
+    if summary.days.is_empty() {
+        out.push_str("Nothing was logged in this period.\n");
+    }
+
+    for day in summary.days.iter() {
+        out.push_str(&format!(
+            "## {} ({})\n\n",
+            day.date.format("%A %-e %B, %Y"),
+            format_minutes(day.minutes, day.raw_minutes)
+        ));
+
+        for project in day.projects.iter() {
+            out.push_str(&format!(
+                "### {} ({})\n\n",
+                escape(&project.name),
+                format_minutes(project.minutes, project.raw_minutes)
+            ));
+
+            push_tasks(&mut out, &project.tasks, options);
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+fn header(name: &str, options: &RenderOptions) -> String {
+    let mut out = format!("# {}\n\n", escape(name));
+
+    if options.range.is_bounded() {
+        out.push_str(&format!("_{}_\n\n", options.range.describe()));
+    }
+
+    if let Some(rounding) = options.rounding {
+        out.push_str(&format!("_Rounded {}_\n\n", rounding.describe()));
+    }
+
+    out
+}
+
+fn push_tasks(out: &mut String, tasks: &[SummarizedTask], options: &RenderOptions) {
+    for task in tasks {
+        out.push_str(&format!(
+            "- **{}** ({}){}\n",
+            escape(&task.name),
+            format_minutes(task.minutes, task.raw_minutes),
+            if task.billable { "" } else { " _non-billable_" }
+        ));
+
+        if options.detailed {
+            for entry in task.entries.iter() {
+                out.push_str(&format!(
+                    "  - {} ({})\n",
+                    escape(&entry.name),
+                    format_minutes(entry.minutes, entry.raw_minutes)
+                ));
+            }
+        }
+    }
+}
+
+// The time spent, along with the time logged when rounding changed it
+fn format_minutes(minutes: usize, raw_minutes: usize) -> String {
+    if minutes != raw_minutes {
+        format!(
+            "{}, raw {}",
+            utils::format_time(minutes),
+            utils::format_time(raw_minutes)
+        )
+    } else {
+        utils::format_time(minutes)
+    }
+}
+
+// Task names and messages are free text, keep them from turning into markup
+pub fn escape(input: &str) -> String {
+    let mut escaped = String::new();
+
+    for c in input.chars() {
+        if "\\`*_[]<>#|".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
@@ -0,0 +1,256 @@
+use super::{RenderOptions, ReportRenderer, Rollup};
+use crate::summary::{
+    CombinedDay, CombinedSummary, SummarizedDay, SummarizedMonth, SummarizedWeek, Summary,
+};
+use crate::utils;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{NaiveDate, NaiveDateTime};
+use handlebars::{
+    handlebars_helper, Context, Handlebars, Helper, HelperResult, Output, RenderContext,
+    RenderError,
+};
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// A timesheet in a layout of the user's own, written as a Handlebars
+// template. Templates are found by path, or by name in the templates
+// directory (`~/.config/timecop/templates/<name>.hbs`).
+pub struct TemplateRenderer {
+    name: String,
+    source: String,
+    // Only HTML templates get their values escaped
+    html: bool,
+}
+
+// Everything a template has access to
+#[derive(Serialize)]
+struct TemplateContext<'a> {
+    id: usize,
+    name: &'a str,
+    minutes: usize,
+    billable_minutes: usize,
+    non_billable_minutes: usize,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    detailed: bool,
+    days: &'a [SummarizedDay],
+    weeks: Vec<SummarizedWeek>,
+    months: Vec<SummarizedMonth>,
+}
+
+// What a template gets for several projects, the projects are within the days
+#[derive(Serialize)]
+struct CombinedContext<'a> {
+    name: &'a str,
+    minutes: usize,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    detailed: bool,
+    days: &'a [CombinedDay],
+}
+
+impl TemplateRenderer {
+    pub fn load(name_or_path: &str) -> Result<TemplateRenderer, String> {
+        let path = find_template(name_or_path).ok_or_else(|| {
+            format!(
+                "There is no template named `{}`, templates are looked up in {}",
+                name_or_path,
+                templates_dir().display()
+            )
+        })?;
+
+        let source = fs::read_to_string(&path)
+            .map_err(|err| format!("Unable to read {}: {}", path.display(), err))?;
+
+        let html = path
+            .extension()
+            .is_some_and(|ext| ext == "html" || ext == "htm");
+
+        Ok(TemplateRenderer {
+            name: name_or_path.to_string(),
+            source,
+            html,
+        })
+    }
+
+    fn render_context<T: Serialize>(&self, context: &T, out: &mut dyn io::Write) -> io::Result<()> {
+        let mut handlebars = Handlebars::new();
+        handlebars.set_strict_mode(false);
+        if !self.html {
+            handlebars.register_escape_fn(handlebars::no_escape);
+        }
+        handlebars.register_helper("format_time", Box::new(format_time));
+        handlebars.register_helper("hours", Box::new(hours));
+        handlebars.register_helper("format_date", Box::new(format_date));
+
+        handlebars
+            .register_template_string(&self.name, &self.source)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+
+        let rendered = handlebars
+            .render(&self.name, context)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+
+        out.write_all(rendered.as_bytes())
+    }
+}
+
+impl ReportRenderer for TemplateRenderer {
+    fn name(&self) -> &'static str {
+        "template"
+    }
+
+    // A template is free to use any of the rollups
+    fn supports(&self, _by: Rollup) -> bool {
+        true
+    }
+
+    fn render(
+        &self,
+        summary: &Summary,
+        options: &RenderOptions,
+        out: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        let context = TemplateContext {
+            id: summary.id,
+            name: &summary.name,
+            minutes: summary.days.iter().map(|d| d.minutes).sum(),
+            billable_minutes: summary.billable_minutes(),
+            non_billable_minutes: summary.non_billable_minutes(),
+            from: options.range.from,
+            to: options.range.to,
+            detailed: options.detailed,
+            days: &summary.days,
+            weeks: summary.weeks(options.week_start, options.range),
+            months: summary.months(),
+        };
+
+        self.render_context(&context, out)
+    }
+
+    fn render_combined(
+        &self,
+        summary: &CombinedSummary,
+        options: &RenderOptions,
+        out: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        let context = CombinedContext {
+            name: &summary.name,
+            minutes: summary.days.iter().map(|d| d.minutes).sum(),
+            from: options.range.from,
+            to: options.range.to,
+            detailed: options.detailed,
+            days: &summary.days,
+        };
+
+        self.render_context(&context, out)
+    }
+}
+
+pub fn templates_dir() -> PathBuf {
+    let mut path: PathBuf = home::home_dir().unwrap();
+    path.push(".config");
+    path.push("timecop");
+    path.push("templates");
+    path
+}
+
+// A path to a file wins, otherwise look for the name in the templates directory
+fn find_template(name_or_path: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(name_or_path);
+    if path.is_file() {
+        return Some(path);
+    }
+
+    let dir = templates_dir();
+    [
+        name_or_path.to_string(),
+        format!("{}.hbs", name_or_path),
+        format!("{}.html", name_or_path),
+    ]
+    .iter()
+    .map(|name| dir.join(name))
+    .find(|path| path.is_file())
+}
+
+// {{format_time minutes}} gives 01h30m
+handlebars_helper!(format_time: |minutes: u64| utils::format_time(minutes as usize));
+
+// {{hours minutes}} gives 1.50, for the timesheets that want decimal hours
+handlebars_helper!(hours: |minutes: u64| format!("{:.2}", minutes as f64 / 60.0));
+
+// {{format_date date "%A %-e %B"}} formats a date, or a timestamp, like strftime
+fn format_date(
+    helper: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let value = helper
+        .param(0)
+        .and_then(|p| p.value().as_str())
+        .ok_or_else(|| RenderError::new("format_date needs a date"))?;
+    let format = helper
+        .param(1)
+        .and_then(|p| p.value().as_str())
+        .unwrap_or("%Y-%m-%d");
+
+    // chrono panics on an unknown specifier once it gets to formatting
+    if StrftimeItems::new(format).any(|item| item == Item::Error) {
+        return Err(RenderError::new(format!(
+            "`{}` is not a valid date format",
+            format
+        )));
+    }
+
+    let formatted = if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        date.format(format).to_string()
+    } else if let Ok(datetime) = value.parse::<chrono::DateTime<chrono::Utc>>() {
+        utils::utc_to_local(&datetime.naive_utc())
+            .format(format)
+            .to_string()
+    } else if let Ok(datetime) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S") {
+        datetime.format(format).to_string()
+    } else {
+        return Err(RenderError::new(format!("`{}` is not a date", value)));
+    };
+
+    out.write(&formatted)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(source: &str) -> io::Result<String> {
+        let renderer = TemplateRenderer {
+            name: "test".to_string(),
+            source: source.to_string(),
+            html: false,
+        };
+
+        let mut out: Vec<u8> = Vec::new();
+        renderer.render_context(&serde_json::json!({ "date": "2021-06-01" }), &mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(render("{{format_date date}}").unwrap(), "2021-06-01");
+        assert_eq!(
+            render("{{format_date date \"%A %-e %B\"}}").unwrap(),
+            "Tuesday 1 June"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_date_formats() {
+        assert!(render("{{format_date date \"%Q\"}}").is_err());
+        assert!(render("{{format_date date \"%\"}}").is_err());
+    }
+}
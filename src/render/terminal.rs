@@ -0,0 +1,325 @@
+use super::{RenderOptions, ReportRenderer, Rollup};
+use crate::summary::{CombinedSummary, SummarizedTask, Summary};
+use crate::utils;
+
+use std::io;
+use termion::{color, style};
+
+// The colorful output meant to be read in the terminal
+pub struct TerminalRenderer;
+
+impl ReportRenderer for TerminalRenderer {
+    fn name(&self) -> &'static str {
+        "text"
+    }
+
+    fn supports(&self, _by: Rollup) -> bool {
+        true
+    }
+
+    fn render(
+        &self,
+        summary: &Summary,
+        options: &RenderOptions,
+        out: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        let styles = Styles::new(options.color);
+
+        write_title(out, &styles, "Project Summary:", &summary.name)?;
+        if options.range.is_bounded() {
+            write_title(out, &styles, "Period:", &options.range.describe())?;
+        }
+        if let Some(rounding) = options.rounding {
+            write_title(out, &styles, "Rounded:", &rounding.describe())?;
+        }
+        writeln!(out)?;
+
+        if summary.days.is_empty() {
+            writeln!(out, "Nothing was logged in this period.")?;
+            writeln!(out)?;
+        }
+
+        match options.by {
+            Rollup::Day => {
+                for day in summary.days.iter() {
+                    let day_name = format!("{}", day.date.format("%A"));
+                    let date = format!("({})", day.date.format("%-e %B, %Y"));
+                    write_title(out, &styles, &day_name, &date)?;
+
+                    write_tasks(out, &styles, &day.tasks, 1, options.detailed, false)?;
+                    if options.rounding.is_some() {
+                        write_total(out, &styles, day.minutes, day.raw_minutes)?;
+                    }
+                    writeln!(out)?;
+                }
+            }
+            Rollup::Week => {
+                for week in summary.weeks(options.week_start, options.range) {
+                    let week_name = format!("Week {}", week.week);
+                    let dates = format!(
+                        "({} - {})",
+                        week.from.format("%-e %B"),
+                        week.to.format("%-e %B, %Y")
+                    );
+                    write_title(out, &styles, &week_name, &dates)?;
+
+                    write_tasks(out, &styles, &week.tasks, 1, options.detailed, true)?;
+                    write_total(out, &styles, week.minutes, week.raw_minutes)?;
+                    writeln!(out)?;
+                }
+            }
+            Rollup::Month => {
+                for month in summary.months() {
+                    let month_name = format!("{}", month.first_day().format("%B"));
+                    let year = format!("({})", month.year);
+                    write_title(out, &styles, &month_name, &year)?;
+
+                    write_tasks(out, &styles, &month.tasks, 1, options.detailed, true)?;
+                    write_total(out, &styles, month.minutes, month.raw_minutes)?;
+                    writeln!(out)?;
+                }
+            }
+        }
+
+        // Only worth splitting up when some of it is not billable
+        let non_billable = summary.non_billable_minutes();
+        if non_billable > 0 {
+            let billable = utils::format_time(summary.billable_minutes());
+            write_title(out, &styles, "Billable:", &billable)?;
+            write_title(
+                out,
+                &styles,
+                "Non-billable:",
+                &utils::format_time(non_billable),
+            )?;
+        }
+
+        // With rounding, the totals are what it is all about
+        if options.rounding.is_some() && !summary.days.is_empty() {
+            let minutes: usize = summary.days.iter().map(|d| d.minutes).sum();
+            let raw: usize = summary.days.iter().map(|d| d.raw_minutes).sum();
+            let total = format!(
+                "{}{}",
+                utils::format_time(minutes),
+                format_raw(&styles, minutes, raw)
+            );
+            write_title(out, &styles, "Total:", &total)?;
+        }
+
+        Ok(())
+    }
+
+    fn render_combined(
+        &self,
+        summary: &CombinedSummary,
+        options: &RenderOptions,
+        out: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        let styles = Styles::new(options.color);
+
+        write_title(out, &styles, "Report:", &summary.name)?;
+        if options.range.is_bounded() {
+            write_title(out, &styles, "Period:", &options.range.describe())?;
+        }
+        if let Some(rounding) = options.rounding {
+            write_title(out, &styles, "Rounded:", &rounding.describe())?;
+        }
+        writeln!(out)?;
+
+        if summary.days.is_empty() {
+            writeln!(out, "Nothing was logged in this period.")?;
+            writeln!(out)?;
+        }
+
+        for day in summary.days.iter() {
+            let day_name = format!("{}", day.date.format("%A"));
+            let date = format!(
+                "({}) [{}]{}",
+                day.date.format("%-e %B, %Y"),
+                utils::format_time(day.minutes),
+                format_raw(&styles, day.minutes, day.raw_minutes)
+            );
+            write_title(out, &styles, &day_name, &date)?;
+
+            for project in day.projects.iter() {
+                writeln!(
+                    out,
+                    "  {}{}{}{} [{}]{}",
+                    styles.blue,
+                    styles.bold,
+                    project.name,
+                    styles.reset,
+                    utils::format_time(project.minutes),
+                    format_raw(&styles, project.minutes, project.raw_minutes),
+                )?;
+
+                write_tasks(out, &styles, &project.tasks, 2, options.detailed, false)?;
+            }
+            writeln!(out)?;
+        }
+
+        Ok(())
+    }
+}
+
+// The escape codes to use, all of them empty when colors are turned off
+pub struct Styles {
+    pub white: String,
+    pub blue: String,
+    pub dim: String,
+    pub bold: String,
+    pub reset: String,
+}
+
+impl Styles {
+    pub fn new(color: bool) -> Styles {
+        if !color {
+            return Styles {
+                white: "".to_string(),
+                blue: "".to_string(),
+                dim: "".to_string(),
+                bold: "".to_string(),
+                reset: "".to_string(),
+            };
+        }
+
+        Styles {
+            white: color::Fg(color::LightWhite).to_string(),
+            blue: color::Fg(color::LightBlue).to_string(),
+            dim: color::Fg(color::LightBlack).to_string(),
+            bold: style::Bold.to_string(),
+            reset: style::Reset.to_string(),
+        }
+    }
+}
+
+// A bold white title followed by a message, like `utils::info_msg_compact`
+pub fn write_title(
+    out: &mut dyn io::Write,
+    styles: &Styles,
+    title: &str,
+    message: &str,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "{}{}{}{} {}",
+        styles.white, styles.bold, title, styles.reset, message
+    )
+}
+
+// Write the tasks indented to the given depth, under a day or a longer period.
+// With details the total of a task is only shown for the longer periods, a
+// day lists the entries under the name of the task.
+fn write_tasks(
+    out: &mut dyn io::Write,
+    styles: &Styles,
+    tasks: &[SummarizedTask],
+    depth: usize,
+    detailed: bool,
+    totals: bool,
+) -> io::Result<()> {
+    let indent = "  ".repeat(depth);
+
+    for task in tasks {
+        if detailed {
+            let total = if totals {
+                format!(
+                    " [{}]{}",
+                    utils::format_time(task.minutes),
+                    format_raw(styles, task.minutes, task.raw_minutes)
+                )
+            } else {
+                "".to_string()
+            };
+
+            writeln!(
+                out,
+                "{}{}{}{}{}{}{}",
+                indent,
+                styles.white,
+                styles.bold,
+                task.name,
+                styles.reset,
+                total,
+                format_non_billable(styles, task.billable),
+            )?;
+
+            for entry in task.entries.iter() {
+                writeln!(
+                    out,
+                    "{}  [{}{}{}] {}{}{}",
+                    indent,
+                    styles.white,
+                    utils::format_time(entry.minutes),
+                    styles.reset,
+                    entry.name,
+                    format_raw(styles, entry.minutes, entry.raw_minutes),
+                    format_intervals(styles, entry.intervals),
+                )?;
+            }
+        } else {
+            writeln!(
+                out,
+                "{}[{}{}{}] {}{}{}",
+                indent,
+                styles.white,
+                utils::format_time(task.minutes),
+                styles.reset,
+                task.name,
+                format_raw(styles, task.minutes, task.raw_minutes),
+                format_non_billable(styles, task.billable),
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_total(
+    out: &mut dyn io::Write,
+    styles: &Styles,
+    minutes: usize,
+    raw_minutes: usize,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "  {}[{}]{} {}Total{}{}",
+        styles.white,
+        utils::format_time(minutes),
+        styles.reset,
+        styles.bold,
+        styles.reset,
+        format_raw(styles, minutes, raw_minutes),
+    )
+}
+
+// The time actually logged, when rounding made a difference
+fn format_raw(styles: &Styles, minutes: usize, raw_minutes: usize) -> String {
+    if minutes != raw_minutes {
+        format!(
+            " {}(raw {}){}",
+            styles.dim,
+            utils::format_time(raw_minutes),
+            styles.reset
+        )
+    } else {
+        "".to_string()
+    }
+}
+
+fn format_non_billable(styles: &Styles, billable: bool) -> String {
+    if billable {
+        "".to_string()
+    } else {
+        format!(" {}(non-billable){}", styles.dim, styles.reset)
+    }
+}
+
+// Only worth mentioning when the work was actually interrupted
+fn format_intervals(styles: &Styles, intervals: usize) -> String {
+    if intervals > 1 {
+        format!(" {}({} intervals){}", styles.dim, intervals, styles.reset)
+    } else {
+        "".to_string()
+    }
+}
@@ -1,73 +1,380 @@
 use crate::database;
+use crate::dates::{DateRange, Zone};
 use crate::project::Project;
 use crate::utils;
 
-use chrono::NaiveDate;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+use serde::Serialize;
+use std::cmp::{Ordering, Reverse};
 use std::vec::Vec;
 
-#[derive(Debug)]
+// What to summarize, and how
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SummaryOptions {
+    pub zone: Zone,
+    pub range: DateRange,
+    pub rounding: Option<Rounding>,
+    // Leave out the time that is not billable
+    pub billable_only: bool,
+}
+
+// Round the time spent to a multiple of `minutes`, for those who bill in
+// increments. The scope decides what gets rounded: every log entry, the
+// total of a task on a day, or the total of a day.
+#[derive(Debug, Clone, Copy)]
+pub struct Rounding {
+    pub minutes: usize,
+    pub mode: RoundingMode,
+    pub scope: RoundingScope,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RoundingMode {
+    Up,
+    Down,
+    Nearest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RoundingScope {
+    Entry,
+    Task,
+    Day,
+}
+
+impl Rounding {
+    // Like `up to 15 minutes per task`
+    pub fn describe(&self) -> String {
+        let mode = match self.mode {
+            RoundingMode::Up => "up to",
+            RoundingMode::Down => "down to",
+            RoundingMode::Nearest => "to the nearest",
+        };
+
+        let scope = match self.scope {
+            RoundingScope::Entry => "log entry",
+            RoundingScope::Task => "task per day",
+            RoundingScope::Day => "day",
+        };
+
+        format!("{} {} minutes per {}", mode, self.minutes, scope)
+    }
+
+    pub fn round(&self, minutes: usize) -> usize {
+        if self.minutes == 0 {
+            return minutes;
+        }
+
+        let remainder = minutes % self.minutes;
+        if remainder == 0 {
+            return minutes;
+        }
+
+        let down = minutes - remainder;
+        match self.mode {
+            RoundingMode::Up => down + self.minutes,
+            RoundingMode::Down => down,
+            RoundingMode::Nearest if remainder * 2 >= self.minutes => down + self.minutes,
+            RoundingMode::Nearest => down,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
 pub struct Summary {
     pub id: usize,
     pub name: String,
     pub days: Vec<SummarizedDay>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct SummarizedDay {
     pub date: NaiveDate,
     pub minutes: usize,
+    pub raw_minutes: usize,
+    pub tasks: Vec<SummarizedTask>,
+}
+
+// A summary of several projects at once, by day
+#[derive(Debug, Serialize)]
+pub struct CombinedSummary {
+    pub name: String,
+    pub days: Vec<CombinedDay>,
+}
+
+// The days of several projects combined, with the projects within a day
+#[derive(Debug, Serialize)]
+pub struct CombinedDay {
+    pub date: NaiveDate,
+    pub minutes: usize,
+    pub raw_minutes: usize,
+    pub projects: Vec<SummarizedProject>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SummarizedProject {
+    pub name: String,
+    pub minutes: usize,
+    pub raw_minutes: usize,
+    pub tasks: Vec<SummarizedTask>,
+}
+
+// The days of one week rolled up, labeled with the ISO week number
+#[derive(Debug, Serialize)]
+pub struct SummarizedWeek {
+    pub year: i32,
+    pub week: u32,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub minutes: usize,
+    pub raw_minutes: usize,
+    pub tasks: Vec<SummarizedTask>,
+}
+
+// The days of one calendar month rolled up
+#[derive(Debug, Serialize)]
+pub struct SummarizedMonth {
+    pub year: i32,
+    pub month: u32,
+    pub minutes: usize,
+    pub raw_minutes: usize,
     pub tasks: Vec<SummarizedTask>,
 }
 
-#[derive(Debug)]
+// The billable and the non-billable time on a task are kept apart, so a task
+// can show up twice within the same period.
+#[derive(Debug, Clone, Serialize)]
 pub struct SummarizedTask {
     pub id: usize,
     pub name: String,
     pub minutes: usize,
+    pub raw_minutes: usize,
+    pub billable: bool,
     pub entries: Vec<SummarizedTaskEntry>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SummarizedTaskEntry {
+    pub id: usize,
     pub name: String,
     pub minutes: usize,
+    pub raw_minutes: usize,
+    pub intervals: usize,
+    pub billable: bool,
+    pub inserted_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Summary {
+    // Roll the days up into weeks starting on the given day, most recent first.
+    // The first and last week only cover the part that falls within the range.
+    pub fn weeks(&self, week_start: Weekday, range: DateRange) -> Vec<SummarizedWeek> {
+        let mut results: Vec<SummarizedWeek> = Vec::new();
+
+        for day in self.days.iter() {
+            let offset = (7 + day.date.weekday().num_days_from_monday()
+                - week_start.num_days_from_monday())
+                % 7;
+            let from = day.date - Duration::days(offset as i64);
+
+            let week = match results.iter().position(|w| w.from == from) {
+                Some(index) => &mut results[index],
+                None => {
+                    // The middle of the week decides the ISO week, which makes
+                    // weeks that don't start on a monday end up close enough.
+                    let iso = (from + Duration::days(3)).iso_week();
+                    results.push(SummarizedWeek {
+                        year: iso.year(),
+                        week: iso.week(),
+                        from,
+                        to: from + Duration::days(6),
+                        minutes: 0,
+                        raw_minutes: 0,
+                        tasks: Vec::new(),
+                    });
+                    results.last_mut().unwrap()
+                }
+            };
+
+            week.minutes += day.minutes;
+            week.raw_minutes += day.raw_minutes;
+            merge_tasks(&mut week.tasks, &day.tasks);
+        }
+
+        for week in results.iter_mut() {
+            week.from = range.from.map_or(week.from, |from| from.max(week.from));
+            week.to = range.to.map_or(week.to, |to| to.min(week.to));
+        }
+
+        results
+    }
+
+    // Roll the days up into calendar months, most recent first
+    pub fn months(&self) -> Vec<SummarizedMonth> {
+        let mut results: Vec<SummarizedMonth> = Vec::new();
+
+        for day in self.days.iter() {
+            let (year, month) = (day.date.year(), day.date.month());
+
+            let summary = match results
+                .iter()
+                .position(|m| m.year == year && m.month == month)
+            {
+                Some(index) => &mut results[index],
+                None => {
+                    results.push(SummarizedMonth {
+                        year,
+                        month,
+                        minutes: 0,
+                        raw_minutes: 0,
+                        tasks: Vec::new(),
+                    });
+                    results.last_mut().unwrap()
+                }
+            };
+
+            summary.minutes += day.minutes;
+            summary.raw_minutes += day.raw_minutes;
+            merge_tasks(&mut summary.tasks, &day.tasks);
+        }
+
+        results
+    }
+
+    pub fn billable_minutes(&self) -> usize {
+        self.minutes_where(true)
+    }
+
+    pub fn non_billable_minutes(&self) -> usize {
+        self.minutes_where(false)
+    }
+
+    fn minutes_where(&self, billable: bool) -> usize {
+        self.days
+            .iter()
+            .flat_map(|d| d.tasks.iter())
+            .filter(|t| t.billable == billable)
+            .map(|t| t.minutes)
+            .sum()
+    }
+
+    // Every task over the whole period, with the time spent on it added up
+    pub fn tasks(&self) -> Vec<SummarizedTask> {
+        let mut tasks: Vec<SummarizedTask> = Vec::new();
+
+        for day in self.days.iter() {
+            merge_tasks(&mut tasks, &day.tasks);
+        }
+
+        tasks
+    }
 }
 
-pub fn for_project(project: &Project) -> Summary {
+impl SummarizedMonth {
+    pub fn first_day(&self) -> NaiveDate {
+        NaiveDate::from_ymd(self.year, self.month, 1)
+    }
+}
+
+// Summarize every project and combine them by day, the most recent day
+// first and within a day the projects in the order they were given.
+pub fn for_projects(projects: &[Project], options: &SummaryOptions) -> CombinedSummary {
+    let mut results: Vec<CombinedDay> = Vec::new();
+
+    for project in projects {
+        let summary = for_project(project, options);
+
+        for day in summary.days {
+            let combined = match results.iter().position(|d| d.date == day.date) {
+                Some(index) => &mut results[index],
+                None => {
+                    results.push(CombinedDay {
+                        date: day.date,
+                        minutes: 0,
+                        raw_minutes: 0,
+                        projects: Vec::new(),
+                    });
+                    results.last_mut().unwrap()
+                }
+            };
+
+            combined.minutes += day.minutes;
+            combined.raw_minutes += day.raw_minutes;
+            combined.projects.push(SummarizedProject {
+                name: summary.name.to_string(),
+                minutes: day.minutes,
+                raw_minutes: day.raw_minutes,
+                tasks: day.tasks,
+            });
+        }
+    }
+
+    results.sort_by_key(|day| Reverse(day.date));
+
+    let name = match projects {
+        [project] => project.name.to_string(),
+        _ => format!("{} projects", projects.len()),
+    };
+
+    CombinedSummary {
+        name,
+        days: results,
+    }
+}
+
+pub fn for_project(project: &Project, options: &SummaryOptions) -> Summary {
     let mut results: Vec<SummarizedDay> = Vec::new();
+    let (column, from, to) = range_bounds(&options.zone, &options.range);
 
     database::with_db(|db| {
-        // List all projects
+        // List all log entries, the grouping by day happens in the user's
+        // timezone which SQLite doesn't know about.
         let mut cursor = db
-            .prepare(
+            .prepare(format!(
                 "
                     SELECT
                         t.id,
                         t.name,
                         l.name as log,
                         l.minutes,
-                        l.inserted_at as date,
+                        l.inserted_at,
+                        l.utc_offset,
                         (
                             SELECT
-                                SUM(l2.minutes)
-                            FROM task_logs l2
-                            WHERE l2.task_id = l.task_id
-                            AND DATE(l2.inserted_at) = DATE(l.inserted_at)
-                        ) as minutes_total
+                                COUNT(i.id)
+                            FROM task_log_intervals i
+                            WHERE i.task_log_id = l.id
+                        ) as intervals,
+                        l.id,
+                        l.updated_at,
+                        COALESCE(l.billable, t.billable, p.billable) as billable
                     FROM task_logs l
                     LEFT JOIN tasks t ON t.id = l.task_id
-                    WHERE t.project_id = ?
-                    GROUP BY l.id, DATE(l.inserted_at)
-                    ORDER BY DATE(l.inserted_at) DESC, t.id DESC;",
-            )
+                    LEFT JOIN projects p ON p.id = t.project_id
+                    WHERE t.project_id = ?1
+                        AND (?2 IS NULL OR {column} >= ?2)
+                        AND (?3 IS NULL OR {column} < ?3)
+                        AND (?4 = 0 OR COALESCE(l.billable, t.billable, p.billable) = 1)
+                    ORDER BY l.inserted_at ASC, l.id ASC;",
+                column = column
+            ))
             .unwrap()
             .into_cursor();
 
         cursor
-            .bind(&[sqlite::Value::Integer(project.id as i64)])
+            .bind(&[
+                sqlite::Value::Integer(project.id as i64),
+                from.clone(),
+                to.clone(),
+                sqlite::Value::Integer(options.billable_only as i64),
+            ])
             .unwrap();
 
-        results = process_summary(cursor);
+        results = process_summary(cursor, &options.zone);
+
+        if let Some(rounding) = options.rounding {
+            round_days(&mut results, &rounding);
+        }
     });
 
     Summary {
@@ -77,60 +384,84 @@ pub fn for_project(project: &Project) -> Summary {
     }
 }
 
-fn process_summary(mut cursor: sqlite::Cursor) -> Vec<SummarizedDay> {
+// The range is turned into a half-open range on the column to compare with.
+// With the logged offsets that is the local date of every single entry,
+// otherwise the days of the zone can be turned into UTC timestamps.
+fn range_bounds(zone: &Zone, range: &DateRange) -> (&'static str, sqlite::Value, sqlite::Value) {
+    let to_value = |date: Option<NaiveDate>| match date {
+        None => sqlite::Value::Null,
+        Some(date) => match zone {
+            Zone::Logged => sqlite::Value::String(date.format("%Y-%m-%d").to_string()),
+            _ => match zone.start_of_day(date) {
+                Some(datetime) => sqlite::Value::String(utils::datetime_to_sql(&datetime)),
+                None => sqlite::Value::Null,
+            },
+        },
+    };
+
+    let column = match zone {
+        Zone::Logged => "DATE(l.inserted_at, l.utc_offset || ' minutes')",
+        _ => "l.inserted_at",
+    };
+
+    let to = range.to.map(|to| to + Duration::days(1));
+    (column, to_value(range.from), to_value(to))
+}
+
+// Group the entries by day and then by task, the most recent day first
+// and within a day the most recent task first.
+fn process_summary(mut cursor: sqlite::Cursor, zone: &Zone) -> Vec<SummarizedDay> {
     let mut results: Vec<SummarizedDay> = Vec::new();
 
     while let Some(row) = cursor.next().unwrap() {
-        let mut summary = process_summary_day(row);
-        let mut task_summary = process_summary_task(row);
+        let summary = process_summary_day(row, zone);
+        let task_summary = process_summary_task(row);
         let task_entry = process_summary_task_entry(row);
-        let last_index = results.len();
-
-        // TODO: There has got to be a better way.
-        if last_index > 0 {
-            let previous = &mut results[last_index - 1];
-            if previous.date == summary.date {
-                previous.minutes += summary.minutes;
-                let last_task_index = previous.tasks.len();
-                if last_task_index > 0 {
-                    let previous_task = &mut previous.tasks[last_task_index - 1];
-                    if previous_task.id == task_summary.id {
-                        previous_task.minutes += task_entry.minutes;
-                        previous_task.entries.push(task_entry);
-                    } else {
-                        task_summary.minutes = task_entry.minutes;
-                        task_summary.entries.push(task_entry);
-                        previous.tasks.push(task_summary);
-                    }
-                } else {
-                    task_summary.minutes = task_entry.minutes;
-                    task_summary.entries.push(task_entry);
-                    previous.tasks.push(task_summary);
-                }
-            } else {
-                task_summary.minutes = task_entry.minutes;
-                task_summary.entries.push(task_entry);
-                summary.tasks.push(task_summary);
+
+        let day = match results.iter().position(|d| d.date == summary.date) {
+            Some(index) => &mut results[index],
+            None => {
                 results.push(summary);
+                results.last_mut().unwrap()
             }
-        } else {
-            task_summary.minutes = task_entry.minutes;
-            task_summary.entries.push(task_entry);
-            summary.tasks.push(task_summary);
-            results.push(summary);
-        }
+        };
+
+        let task = match day
+            .tasks
+            .iter()
+            .position(|t| t.id == task_summary.id && t.billable == task_summary.billable)
+        {
+            Some(index) => &mut day.tasks[index],
+            None => {
+                day.tasks.push(task_summary);
+                day.tasks.last_mut().unwrap()
+            }
+        };
+
+        day.minutes += task_entry.minutes;
+        day.raw_minutes += task_entry.minutes;
+        task.minutes += task_entry.minutes;
+        task.raw_minutes += task_entry.minutes;
+        task.entries.push(task_entry);
+    }
+
+    results.sort_by_key(|day| Reverse(day.date));
+    for day in results.iter_mut() {
+        day.tasks.sort_by(compare_tasks);
     }
 
     results
 }
 
-fn process_summary_day(row: &[sqlite::Value]) -> SummarizedDay {
-    let minutes = row[3].as_integer().unwrap() as usize;
-    let date = utils::sql_to_date(row[4].as_string()).unwrap();
+fn process_summary_day(row: &[sqlite::Value], zone: &Zone) -> SummarizedDay {
+    let inserted_at = utils::sql_to_datetime(row[4].as_string()).unwrap();
+    let utc_offset = row[5].as_integer().unwrap() as i32;
+    let date = zone.localize(&inserted_at, utc_offset).date();
 
     SummarizedDay {
         date,
-        minutes,
+        minutes: 0,
+        raw_minutes: 0,
         tasks: Vec::new(),
     }
 }
@@ -143,6 +474,8 @@ fn process_summary_task(row: &[sqlite::Value]) -> SummarizedTask {
         id,
         name,
         minutes: 0,
+        raw_minutes: 0,
+        billable: row[9].as_integer() != Some(0),
         entries: Vec::new(),
     }
 }
@@ -150,5 +483,174 @@ fn process_summary_task(row: &[sqlite::Value]) -> SummarizedTask {
 fn process_summary_task_entry(row: &[sqlite::Value]) -> SummarizedTaskEntry {
     let name = row[2].as_string().unwrap().to_string();
     let minutes = row[3].as_integer().unwrap() as usize;
-    SummarizedTaskEntry { name, minutes }
+    let intervals = row[6].as_integer().unwrap() as usize;
+    let id = row[7].as_integer().unwrap() as usize;
+    let inserted_at = utils::sql_to_datetime(row[4].as_string()).unwrap();
+    let updated_at = utils::sql_to_datetime(row[8].as_string()).unwrap();
+
+    SummarizedTaskEntry {
+        id,
+        name,
+        minutes,
+        raw_minutes: minutes,
+        intervals,
+        billable: row[9].as_integer() != Some(0),
+        inserted_at: DateTime::from_utc(inserted_at, Utc),
+        updated_at: DateTime::from_utc(updated_at, Utc),
+    }
+}
+
+// Add the tasks of a day to the tasks of a longer period. The days are most
+// recent first, so the entries are prepended to keep them in order.
+fn merge_tasks(tasks: &mut Vec<SummarizedTask>, day_tasks: &[SummarizedTask]) {
+    for day_task in day_tasks {
+        match tasks
+            .iter_mut()
+            .find(|t| t.id == day_task.id && t.billable == day_task.billable)
+        {
+            Some(task) => {
+                task.minutes += day_task.minutes;
+                task.raw_minutes += day_task.raw_minutes;
+                let mut entries = day_task.entries.clone();
+                entries.append(&mut task.entries);
+                task.entries = entries;
+            }
+            None => tasks.push(day_task.clone()),
+        }
+    }
+
+    tasks.sort_by(compare_tasks);
+}
+
+// The most recent task first, with its billable time before the rest
+fn compare_tasks(a: &SummarizedTask, b: &SummarizedTask) -> Ordering {
+    b.id.cmp(&a.id).then(b.billable.cmp(&a.billable))
+}
+
+// Round within the scope, and add the rounded times up from there on. The
+// raw minutes are left alone, so both can be shown when they differ.
+fn round_days(days: &mut [SummarizedDay], rounding: &Rounding) {
+    for day in days.iter_mut() {
+        for task in day.tasks.iter_mut() {
+            if rounding.scope == RoundingScope::Entry {
+                for entry in task.entries.iter_mut() {
+                    entry.minutes = rounding.round(entry.raw_minutes);
+                }
+
+                task.minutes = task.entries.iter().map(|e| e.minutes).sum();
+            }
+        }
+
+        if rounding.scope == RoundingScope::Task {
+            round_tasks(&mut day.tasks, rounding);
+        }
+
+        day.minutes = match rounding.scope {
+            RoundingScope::Day => rounding.round(day.raw_minutes),
+            _ => day.tasks.iter().map(|t| t.minutes).sum(),
+        };
+    }
+}
+
+// The billable and non-billable time of a task are rounded as one, so a task
+// is never rounded twice on the same day. Whatever the rounding adds or takes
+// away ends up on the billable time.
+fn round_tasks(tasks: &mut [SummarizedTask], rounding: &Rounding) {
+    let mut ids: Vec<usize> = tasks.iter().map(|t| t.id).collect();
+    ids.sort_unstable();
+    ids.dedup();
+
+    for id in ids {
+        let raw_minutes: usize = tasks
+            .iter()
+            .filter(|t| t.id == id)
+            .map(|t| t.raw_minutes)
+            .sum();
+        let mut remaining = rounding.round(raw_minutes);
+
+        let mut halves: Vec<&mut SummarizedTask> =
+            tasks.iter_mut().filter(|t| t.id == id).collect();
+        halves.sort_by_key(|t| t.billable);
+
+        let last = halves.len() - 1;
+        for (index, task) in halves.into_iter().enumerate() {
+            task.minutes = if index == last {
+                remaining
+            } else {
+                task.raw_minutes.min(remaining)
+            };
+            remaining -= task.minutes;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: usize, raw_minutes: usize, billable: bool) -> SummarizedTask {
+        SummarizedTask {
+            id,
+            name: format!("Task {}", id),
+            minutes: raw_minutes,
+            raw_minutes,
+            billable,
+            entries: Vec::new(),
+        }
+    }
+
+    fn rounded(tasks: Vec<SummarizedTask>, mode: RoundingMode) -> Vec<(usize, bool, usize)> {
+        let mut days = vec![SummarizedDay {
+            date: NaiveDate::from_ymd(2021, 6, 1),
+            minutes: 0,
+            raw_minutes: tasks.iter().map(|t| t.raw_minutes).sum(),
+            tasks,
+        }];
+        let rounding = Rounding {
+            minutes: 15,
+            mode,
+            scope: RoundingScope::Task,
+        };
+
+        round_days(&mut days, &rounding);
+
+        let day = &days[0];
+        assert_eq!(
+            day.minutes,
+            day.tasks.iter().map(|t| t.minutes).sum::<usize>()
+        );
+        day.tasks
+            .iter()
+            .map(|t| (t.id, t.billable, t.minutes))
+            .collect()
+    }
+
+    #[test]
+    fn rounds_a_task_once_per_day() {
+        assert_eq!(
+            rounded(
+                vec![task(1, 10, true), task(1, 10, false), task(2, 5, true)],
+                RoundingMode::Up
+            ),
+            vec![(1, true, 20), (1, false, 10), (2, true, 15)]
+        );
+    }
+
+    #[test]
+    fn takes_rounding_down_from_the_billable_time_first() {
+        assert_eq!(
+            rounded(
+                vec![task(1, 20, true), task(1, 5, false)],
+                RoundingMode::Down
+            ),
+            vec![(1, true, 10), (1, false, 5)]
+        );
+        assert_eq!(
+            rounded(
+                vec![task(1, 5, true), task(1, 20, false)],
+                RoundingMode::Down
+            ),
+            vec![(1, true, 0), (1, false, 15)]
+        );
+    }
 }
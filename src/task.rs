@@ -1,8 +1,9 @@
 use crate::database;
 use crate::project::Project;
+use crate::timer::Timer;
 use crate::utils;
 
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, Utc};
 use std::vec::Vec;
 
 #[derive(Debug)]
@@ -11,6 +12,12 @@ pub struct Task {
     pub name: String,
     pub inserted_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    // Overrides the hourly rate (in cents) of the project
+    pub rate: Option<i64>,
+    // Overrides whether the time spent on the project is billable
+    pub billable: Option<bool>,
+    // The minutes this task was supposed to take
+    pub estimate: Option<usize>,
 }
 
 impl Task {
@@ -34,8 +41,48 @@ impl Task {
         save_context(project, &self)
     }
 
-    pub fn add_log(&self, minutes: usize, message: String) {
-        save_task_log(&self, minutes, message)
+    // Without `billable` the entry follows its task and project
+    pub fn add_log(
+        &self,
+        minutes: usize,
+        message: String,
+        billable: Option<bool>,
+    ) -> Option<usize> {
+        save_task_log(&self, minutes, message, None, billable)
+    }
+
+    pub fn add_log_at(
+        &self,
+        minutes: usize,
+        message: String,
+        inserted_at: &NaiveDateTime,
+        billable: Option<bool>,
+    ) -> Option<usize> {
+        save_task_log(&self, minutes, message, Some(inserted_at), billable)
+    }
+
+    pub fn logged_minutes(&self) -> usize {
+        logged_minutes(self)
+    }
+
+    pub fn minutes_since_last_log(&self) -> Option<usize> {
+        minutes_since_last_log(self)
+    }
+
+    pub fn start_timer(&self) -> Option<Timer> {
+        Timer::start_for(self)
+    }
+
+    pub fn set_rate(&self, rate: Option<i64>) {
+        save_rate(self, rate)
+    }
+
+    pub fn set_billable(&self, billable: Option<bool>) {
+        save_billable(self, billable)
+    }
+
+    pub fn set_estimate(&self, estimate: Option<usize>) {
+        save_estimate(self, estimate)
     }
 
     pub fn touch(&self) {
@@ -45,13 +92,15 @@ impl Task {
 
 fn find_existing_task() -> Option<Task> {
     let mut result: Option<Task> = None;
-    let remote_branch = get_remote_branch();
+
+    // Outside of a repository, or without a branch, there is no task to find
+    let remote_branch = get_remote_branch()?;
 
     database::with_db(|db| {
         let mut cursor = db
             .prepare(
                 "
-                SELECT t.id, t.name, t.inserted_at, t.updated_at
+                SELECT t.id, t.name, t.inserted_at, t.updated_at, t.rate, t.billable, t.estimate
                 FROM tasks t
                 LEFT JOIN contexts c ON c.task_id = t.id
                 WHERE c.context = ?
@@ -77,7 +126,7 @@ fn list_project_tasks(project: &Project) -> Vec<Task> {
         let mut cursor = db
             .prepare(
                 "
-                SELECT t.id, t.name, t.inserted_at, t.updated_at
+                SELECT t.id, t.name, t.inserted_at, t.updated_at, t.rate, t.billable, t.estimate
                 FROM tasks t
                 WHERE t.project_id = ?
                 ORDER BY t.updated_at DESC;",
@@ -102,7 +151,9 @@ fn create_task(project: &Project, name: String) -> Option<Task> {
 
     database::with_db(|db| {
         let mut cursor = db
-            .prepare("REPLACE INTO tasks VALUES (null, ?, ?, DATETIME(), DATETIME());")
+            .prepare(
+                "REPLACE INTO tasks (project_id, name, inserted_at, updated_at) VALUES (?, ?, DATETIME(), DATETIME());",
+            )
             .unwrap()
             .into_cursor();
 
@@ -117,7 +168,7 @@ fn create_task(project: &Project, name: String) -> Option<Task> {
             cursor = db
                 .prepare(
                     "
-                SELECT t.id, t.name, t.inserted_at, t.updated_at
+                SELECT t.id, t.name, t.inserted_at, t.updated_at, t.rate, t.billable, t.estimate
                 FROM tasks t
                 WHERE t.id IN(SELECT last_insert_rowid());
                 ",
@@ -137,9 +188,25 @@ fn create_task(project: &Project, name: String) -> Option<Task> {
     result
 }
 
-// This function will attempt to store the current context attaching
-// the remote to this Project
-fn save_task_log(task: &Task, minutes: usize, message: String) {
+// This function will store a new log entry for this Task and
+// return the id of said entry (or None if something goes wrong)
+// Without an explicit (UTC) timestamp the entry is stamped with "now".
+fn save_task_log(
+    task: &Task,
+    minutes: usize,
+    message: String,
+    inserted_at: Option<&NaiveDateTime>,
+    billable: Option<bool>,
+) -> Option<usize> {
+    let mut result: Option<usize> = None;
+
+    // Also store the offset of the local timezone, so we know which day
+    // this was for the user even though the timestamp itself is in UTC.
+    let inserted_at = match inserted_at {
+        Some(datetime) => *datetime,
+        None => Utc::now().naive_utc(),
+    };
+
     database::with_db(|db| {
         let mut cursor = db
             .prepare(
@@ -147,13 +214,17 @@ fn save_task_log(task: &Task, minutes: usize, message: String) {
                   task_id,
                   name,
                   minutes,
+                  utc_offset,
+                  billable,
                   inserted_at,
                   updated_at
                 ) VALUES (
                   ?,
                   ?,
                   ?,
-                  DATETIME(),
+                  ?,
+                  ?,
+                  ?,
                   DATETIME()
                 );",
             )
@@ -165,17 +236,36 @@ fn save_task_log(task: &Task, minutes: usize, message: String) {
                 sqlite::Value::Integer(task.id as i64),
                 sqlite::Value::String(message.to_string()),
                 sqlite::Value::Integer(minutes as i64),
+                sqlite::Value::Integer(utils::local_utc_offset(&inserted_at) as i64),
+                billable.map_or(sqlite::Value::Null, |b| sqlite::Value::Integer(b as i64)),
+                sqlite::Value::String(utils::datetime_to_sql(&inserted_at)),
             ])
             .unwrap();
 
-        cursor.next().unwrap();
+        if cursor.next().is_ok() {
+            cursor = db
+                .prepare("SELECT last_insert_rowid();")
+                .unwrap()
+                .into_cursor();
+
+            if let Ok(Some(row)) = cursor.next() {
+                result = Some(row[0].as_integer().unwrap() as usize);
+            }
+        }
     });
+
+    result
 }
 
 // This function will attempt to store the current context attaching
 // the remote to this Project
 fn save_context(project: &Project, task: &Task) {
-    let remote_branch = get_remote_branch();
+    // Without a branch there is nothing to remember the task for
+    let remote_branch = match get_remote_branch() {
+        Some(remote_branch) => remote_branch,
+        None => return,
+    };
+
     database::with_db(|db| {
         let mut cursor = db
             .prepare("REPLACE INTO contexts VALUES (null, ?, ?, ?, DATETIME(), DATETIME());")
@@ -194,6 +284,56 @@ fn save_context(project: &Project, task: &Task) {
     });
 }
 
+// The time spent on this task over all of its log entries
+fn logged_minutes(task: &Task) -> usize {
+    let mut result: usize = 0;
+
+    database::with_db(|db| {
+        let mut cursor = db
+            .prepare("SELECT COALESCE(SUM(l.minutes), 0) FROM task_logs l WHERE l.task_id = ?;")
+            .unwrap()
+            .into_cursor();
+
+        cursor
+            .bind(&[sqlite::Value::Integer(task.id as i64)])
+            .unwrap();
+
+        if let Ok(Some(row)) = cursor.next() {
+            result = row[0].as_integer().unwrap_or(0) as usize;
+        }
+    });
+
+    result
+}
+
+// How long ago was the last log entry for this task? SQLite stores
+// DATETIME() in UTC, so compare against the current UTC time.
+fn minutes_since_last_log(task: &Task) -> Option<usize> {
+    let mut result: Option<NaiveDateTime> = None;
+
+    database::with_db(|db| {
+        let mut cursor = db
+            .prepare("SELECT MAX(l.inserted_at) FROM task_logs l WHERE l.task_id = ?;")
+            .unwrap()
+            .into_cursor();
+
+        cursor
+            .bind(&[sqlite::Value::Integer(task.id as i64)])
+            .unwrap();
+
+        if let Ok(Some(row)) = cursor.next() {
+            result = utils::sql_to_datetime(row[0].as_string()).ok();
+        }
+    });
+
+    let seconds = (Utc::now().naive_utc() - result?).num_seconds();
+    if seconds < 0 {
+        return None;
+    }
+
+    Some((seconds as usize + 30) / 60)
+}
+
 fn get_by_id(id: usize) -> Option<Task> {
     let mut result: Option<Task> = None;
 
@@ -201,7 +341,7 @@ fn get_by_id(id: usize) -> Option<Task> {
         let mut cursor = db
             .prepare(
                 "
-                SELECT t.id, t.name, t.inserted_at, t.updated_at
+                SELECT t.id, t.name, t.inserted_at, t.updated_at, t.rate, t.billable, t.estimate
                 FROM tasks t
                 WHERE t.id = ?;",
             )
@@ -216,6 +356,63 @@ fn get_by_id(id: usize) -> Option<Task> {
     result
 }
 
+// Store the hourly rate (in cents) for this task, or go back to the one
+// of the project when there is none
+fn save_rate(task: &Task, rate: Option<i64>) {
+    database::with_db(|db| {
+        let mut cursor = db
+            .prepare("UPDATE tasks SET rate = ?, updated_at = DATETIME() WHERE id = ?;")
+            .unwrap()
+            .into_cursor();
+
+        cursor
+            .bind(&[
+                rate.map_or(sqlite::Value::Null, sqlite::Value::Integer),
+                sqlite::Value::Integer(task.id as i64),
+            ])
+            .unwrap();
+
+        cursor.next().unwrap();
+    });
+}
+
+// Mark the task as (non-)billable, or go back to what the project says
+fn save_billable(task: &Task, billable: Option<bool>) {
+    database::with_db(|db| {
+        let mut cursor = db
+            .prepare("UPDATE tasks SET billable = ?, updated_at = DATETIME() WHERE id = ?;")
+            .unwrap()
+            .into_cursor();
+
+        cursor
+            .bind(&[
+                billable.map_or(sqlite::Value::Null, |b| sqlite::Value::Integer(b as i64)),
+                sqlite::Value::Integer(task.id as i64),
+            ])
+            .unwrap();
+
+        cursor.next().unwrap();
+    });
+}
+
+fn save_estimate(task: &Task, estimate: Option<usize>) {
+    database::with_db(|db| {
+        let mut cursor = db
+            .prepare("UPDATE tasks SET estimate = ?, updated_at = DATETIME() WHERE id = ?;")
+            .unwrap()
+            .into_cursor();
+
+        cursor
+            .bind(&[
+                estimate.map_or(sqlite::Value::Null, |e| sqlite::Value::Integer(e as i64)),
+                sqlite::Value::Integer(task.id as i64),
+            ])
+            .unwrap();
+
+        cursor.next().unwrap();
+    });
+}
+
 // This function will "touch" the task, updating it's "last updated" timestamp
 // Which should result in more usable sorted projects and tasks in the UI.
 fn touch_task(task: &Task) {
@@ -254,13 +451,14 @@ fn row_to_task(row: Result<Option<&[sqlite::Value]>, sqlite::Error>) -> Option<T
         name: columns[1].as_string().unwrap().to_string(),
         inserted_at: utils::sql_to_datetime(columns[2].as_string()).unwrap(),
         updated_at: utils::sql_to_datetime(columns[3].as_string()).unwrap(),
+        rate: columns[4].as_integer(),
+        billable: columns[5].as_integer().map(|billable| billable != 0),
+        estimate: columns[6].as_integer().map(|estimate| estimate as usize),
     })
 }
 
-fn get_remote_branch() -> String {
-    // If the code comes here these should have been unwrapped before so I am
-    // assuming they're safe at this point.
-    let repo = utils::get_current_repo().unwrap();
-    let (remote, branch) = utils::get_repo_remote_and_branch(repo).unwrap();
-    format!("{}#{}", remote, branch)
+fn get_remote_branch() -> Option<String> {
+    let repo = utils::get_context_repo()?;
+    let (remote, branch) = utils::get_repo_remote_and_branch(repo)?;
+    Some(format!("{}#{}", remote, branch?))
 }
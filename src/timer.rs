@@ -0,0 +1,293 @@
+use crate::database;
+use crate::task::Task;
+use crate::utils;
+
+use chrono::{NaiveDateTime, Utc};
+use std::vec::Vec;
+
+#[derive(Debug)]
+pub struct Timer {
+    pub id: usize,
+    pub task_id: usize,
+    pub started_at: NaiveDateTime,
+}
+
+#[derive(Debug)]
+pub struct TimerInterval {
+    pub started_at: NaiveDateTime,
+    pub stopped_at: Option<NaiveDateTime>,
+}
+
+impl Timer {
+    pub fn find_running() -> Option<Timer> {
+        find_running_timer()
+    }
+
+    pub fn start_for(task: &Task) -> Option<Timer> {
+        start_timer(task)
+    }
+
+    pub fn task(&self) -> Option<Task> {
+        Task::get_by_id(self.task_id)
+    }
+
+    pub fn intervals(&self) -> Vec<TimerInterval> {
+        list_timer_intervals(self)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        !self.intervals().iter().any(|i| i.stopped_at.is_none())
+    }
+
+    pub fn elapsed_minutes(&self) -> usize {
+        elapsed_minutes(self)
+    }
+
+    pub fn pause(&self) {
+        close_timer_interval(self, Utc::now().naive_utc())
+    }
+
+    pub fn pause_at(&self, stopped_at: NaiveDateTime) {
+        close_timer_interval(self, stopped_at)
+    }
+
+    pub fn resume(&self) {
+        open_timer_interval(self)
+    }
+
+    pub fn attach_to_log(&self, task_log_id: usize) {
+        save_task_log_intervals(self, task_log_id)
+    }
+
+    pub fn stop(&self) {
+        delete_timer(self)
+    }
+}
+
+// There is only ever one running timer, but just in case there are more
+// we'll simply pick the one that was started most recently.
+fn find_running_timer() -> Option<Timer> {
+    let mut result: Option<Timer> = None;
+
+    database::with_db(|db| {
+        let mut cursor = db
+            .prepare(
+                "
+                SELECT t.id, t.task_id, t.started_at
+                FROM timers t
+                ORDER BY t.started_at DESC
+                LIMIT 1;",
+            )
+            .unwrap()
+            .into_cursor();
+
+        result = row_to_timer(cursor.next());
+    });
+
+    result
+}
+
+// This function will attempt to start a new timer for the given task and
+// then it will return said Timer (or None if something goes wrong)
+fn start_timer(task: &Task) -> Option<Timer> {
+    let mut result: Option<Timer> = None;
+
+    database::with_db(|db| {
+        let mut cursor = db
+            .prepare("INSERT INTO timers VALUES (null, ?, DATETIME(), DATETIME(), DATETIME());")
+            .unwrap()
+            .into_cursor();
+
+        cursor
+            .bind(&[sqlite::Value::Integer(task.id as i64)])
+            .unwrap();
+
+        if cursor.next().is_ok() {
+            cursor = db
+                .prepare(
+                    "
+                SELECT t.id, t.task_id, t.started_at
+                FROM timers t
+                WHERE t.id IN(SELECT last_insert_rowid());
+                ",
+                )
+                .unwrap()
+                .into_cursor();
+
+            result = row_to_timer(cursor.next());
+        }
+    });
+
+    // A fresh timer starts with its first active interval
+    if let Some(timer) = &result {
+        open_timer_interval(timer);
+    }
+
+    result
+}
+
+fn list_timer_intervals(timer: &Timer) -> Vec<TimerInterval> {
+    let mut results: Vec<TimerInterval> = Vec::new();
+
+    database::with_db(|db| {
+        let mut cursor = db
+            .prepare(
+                "
+                SELECT i.started_at, i.stopped_at
+                FROM timer_intervals i
+                WHERE i.timer_id = ?
+                ORDER BY i.started_at ASC;",
+            )
+            .unwrap()
+            .into_cursor();
+
+        cursor
+            .bind(&[sqlite::Value::Integer(timer.id as i64)])
+            .unwrap();
+
+        while let Some(interval) = row_to_timer_interval(cursor.next()) {
+            results.push(interval);
+        }
+    });
+
+    results
+}
+
+// Start a new active interval, this happens when a timer is started or resumed.
+fn open_timer_interval(timer: &Timer) {
+    database::with_db(|db| {
+        let mut cursor = db
+            .prepare(
+                "INSERT INTO timer_intervals VALUES (null, ?, DATETIME(), null, DATETIME(), DATETIME());",
+            )
+            .unwrap()
+            .into_cursor();
+
+        cursor
+            .bind(&[sqlite::Value::Integer(timer.id as i64)])
+            .unwrap();
+
+        cursor.next().unwrap();
+    });
+}
+
+// Close the active interval(s), this happens when a timer is paused or stopped.
+fn close_timer_interval(timer: &Timer, stopped_at: NaiveDateTime) {
+    database::with_db(|db| {
+        let mut cursor = db
+            .prepare(
+                "
+                UPDATE timer_intervals
+                SET stopped_at = ?, updated_at = DATETIME()
+                WHERE timer_id = ? AND stopped_at IS NULL;",
+            )
+            .unwrap()
+            .into_cursor();
+
+        cursor
+            .bind(&[
+                sqlite::Value::String(utils::datetime_to_sql(&stopped_at)),
+                sqlite::Value::Integer(timer.id as i64),
+            ])
+            .unwrap();
+
+        cursor.next().unwrap();
+    });
+}
+
+// Copy the intervals of this timer over to the log entry it resulted in,
+// that way we can still tell how fragmented the work on it was.
+fn save_task_log_intervals(timer: &Timer, task_log_id: usize) {
+    database::with_db(|db| {
+        let mut cursor = db
+            .prepare(
+                "
+                INSERT INTO task_log_intervals (
+                  task_log_id,
+                  started_at,
+                  stopped_at,
+                  inserted_at,
+                  updated_at
+                )
+                SELECT ?, i.started_at, COALESCE(i.stopped_at, DATETIME()), DATETIME(), DATETIME()
+                FROM timer_intervals i
+                WHERE i.timer_id = ?
+                ORDER BY i.started_at ASC;",
+            )
+            .unwrap()
+            .into_cursor();
+
+        cursor
+            .bind(&[
+                sqlite::Value::Integer(task_log_id as i64),
+                sqlite::Value::Integer(timer.id as i64),
+            ])
+            .unwrap();
+
+        cursor.next().unwrap();
+    });
+}
+
+// Once a timer has been stopped it is no longer needed, the time it
+// measured will live on as a regular task log entry. Its intervals are
+// removed along with it by the foreign key.
+fn delete_timer(timer: &Timer) {
+    database::with_db(|db| {
+        let mut cursor = db
+            .prepare("DELETE FROM timers WHERE id = ?;")
+            .unwrap()
+            .into_cursor();
+
+        cursor
+            .bind(&[sqlite::Value::Integer(timer.id as i64)])
+            .unwrap();
+
+        cursor.next().unwrap();
+    });
+}
+
+// SQLite stores DATETIME() in UTC, so compare the active interval against
+// the current UTC time. The sum of all intervals is rounded to the nearest minute.
+fn elapsed_minutes(timer: &Timer) -> usize {
+    let now = Utc::now().naive_utc();
+    let seconds: i64 = timer
+        .intervals()
+        .iter()
+        .map(|i| {
+            (i.stopped_at.unwrap_or(now) - i.started_at)
+                .num_seconds()
+                .max(0)
+        })
+        .sum();
+
+    (seconds as usize + 30) / 60
+}
+
+fn row_to_timer(row: Result<Option<&[sqlite::Value]>, sqlite::Error>) -> Option<Timer> {
+    let columns = match row {
+        Ok(None) => return None,
+        Ok(columns) => columns.unwrap(),
+        Err(_) => return None,
+    };
+
+    Some(Timer {
+        id: columns[0].as_integer().unwrap() as usize,
+        task_id: columns[1].as_integer().unwrap() as usize,
+        started_at: utils::sql_to_datetime(columns[2].as_string()).unwrap(),
+    })
+}
+
+fn row_to_timer_interval(
+    row: Result<Option<&[sqlite::Value]>, sqlite::Error>,
+) -> Option<TimerInterval> {
+    let columns = match row {
+        Ok(None) => return None,
+        Ok(columns) => columns.unwrap(),
+        Err(_) => return None,
+    };
+
+    Some(TimerInterval {
+        started_at: utils::sql_to_datetime(columns[0].as_string()).unwrap(),
+        stopped_at: utils::sql_to_datetime(columns[1].as_string()).ok(),
+    })
+}